@@ -0,0 +1,238 @@
+use crate::deck::Card;
+use crate::shoe::Shoe;
+use crate::table::Event;
+use serde::{ Deserialize, Serialize };
+use std::fmt;
+
+/// A card counting system: a tag for every card value.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CountSystem {
+    HiLo,
+    /// Knock-Out: unbalanced, counting the 7 as low and starting below zero.
+    Ko,
+    HiOptI,
+    OmegaII,
+    Zen,
+}
+
+impl CountSystem {
+    pub const ALL: [CountSystem; 5] = [
+        CountSystem::HiLo,
+        CountSystem::Ko,
+        CountSystem::HiOptI,
+        CountSystem::OmegaII,
+        CountSystem::Zen,
+    ];
+
+    /// What `card` adds to the running count.
+    pub fn tag(&self, card: &Card) -> i32 {
+        let value = card.numeric_value();
+        match self {
+            CountSystem::HiLo => match value {
+                2..=6 => 1,
+                7..=9 => 0,
+                _ => -1,
+            },
+            CountSystem::Ko => match value {
+                2..=7 => 1,
+                8 | 9 => 0,
+                _ => -1,
+            },
+            CountSystem::HiOptI => match value {
+                3..=6 => 1,
+                10 => -1,
+                _ => 0,
+            },
+            CountSystem::OmegaII => match value {
+                2 | 3 | 7 => 1,
+                4..=6 => 2,
+                9 => -1,
+                10 => -2,
+                _ => 0,
+            },
+            CountSystem::Zen => match value {
+                2 | 3 | 7 => 1,
+                4..=6 => 2,
+                10 => -2,
+                11 => -1,
+                _ => 0,
+            },
+        }
+    }
+
+    /// Balanced systems count a full shoe back to zero.
+    pub fn is_balanced(&self) -> bool {
+        *self != CountSystem::Ko
+    }
+
+    /// The running count at the start of a shoe. Knock-Out starts at 4 below zero
+    /// for every deck after the first, so its key count is the same for any shoe.
+    pub fn initial_count(&self, decks: u8) -> i32 {
+        match self {
+            CountSystem::Ko => -4 * (decks as i32 - 1),
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for CountSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CountSystem::HiLo => "Hi-Lo",
+            CountSystem::Ko => "KO",
+            CountSystem::HiOptI => "Hi-Opt I",
+            CountSystem::OmegaII => "Omega II",
+            CountSystem::Zen => "Zen",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Keeps the running count for a shoe from the cards it sees dealt.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Counter {
+    system: CountSystem,
+    decks: u8,
+    running: i32,
+    seen: usize,
+}
+
+impl Counter {
+    pub fn new(system: CountSystem, decks: u8) -> Counter {
+        Counter { system, decks, running: system.initial_count(decks), seen: 0 }
+    }
+
+    pub fn system(&self) -> CountSystem {
+        self.system
+    }
+
+    pub fn running(&self) -> i32 {
+        self.running
+    }
+
+    /// The cards counted since the last shuffle.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The running count per deck left in `shoe`.
+    pub fn true_count(&self, shoe: &Shoe) -> f64 {
+        let decks_left = (shoe.remaining() as f64 / 52.0).max(1.0 / 52.0);
+        self.running as f64 / decks_left
+    }
+
+    pub fn observe(&mut self, card: &Card) {
+        self.running += self.system.tag(card);
+        self.seen += 1;
+    }
+
+    /// Starts the count again for a freshly shuffled shoe.
+    pub fn reset(&mut self) {
+        self.running = self.system.initial_count(self.decks);
+        self.seen = 0;
+    }
+
+    /// Counts every card the table shows face up, including the dealer's hole card once
+    /// it is revealed, and resets when the shoe is shuffled or refilled from the discards.
+    pub fn observe_event(&mut self, event: &Event) {
+        match event {
+            Event::Shuffled | Event::ShoeRefilled => self.reset(),
+            Event::PlayerCard { card, .. }
+            | Event::DealerUpcard { card }
+            | Event::HoleCardRevealed { card, .. }
+            | Event::DealerCard { card, .. } => self.observe(card),
+            _ => (),
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::deck::Deck;
+    use crate::player::{ Action, PlayerList };
+    use crate::rules::TableRules;
+    use crate::table::{ Decision, Phase, Table };
+    use crate::table::tests::stacked_shoe;
+
+    pub fn balanced_systems() {
+        for system in CountSystem::ALL {
+            let mut counter = Counter::new(system, 1);
+            let mut deck = Deck::new();
+            for card in deck.deal(52) {
+                counter.observe(&card);
+            }
+            assert_eq!(counter.seen(), 52);
+            match system.is_balanced() {
+                true => assert_eq!(counter.running(), 0, "{}", system),
+                false => assert_eq!(counter.running(), 4, "{}", system),
+            }
+        }
+        assert_eq!(Counter::new(CountSystem::Ko, 6).running(), -20);
+        assert_eq!(CountSystem::OmegaII.tag(&Card::_test_card(5)), 2);
+        assert_eq!(CountSystem::Zen.tag(&Card::_test_card(11)), -1);
+        assert_eq!(CountSystem::HiOptI.tag(&Card::_test_card(11)), 0);
+    }
+
+    pub fn count_a_round() {
+        // Player: 10, 5 hits a 6; dealer 4 up, 2 in the hole, draws a 9 and a King.
+        let mut table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(1, 100), 10, stacked_shoe(&[10, 4, 5, 2, 6, 9, 10]));
+        let mut counter = Counter::new(CountSystem::HiLo, 6);
+        let mut events = table.start_round().unwrap();
+        while table.phase() != Phase::RoundOver {
+            events.extend(match table.next_decision() {
+                Some(Decision::Bet { seat }) => table.place_bet(seat, 10),
+                Some(Decision::Action { .. }) if table.players()[0].hand_total() < 21 => table.act(Action::Hit),
+                Some(_) => table.act(Action::Stand),
+                None => table.advance(),
+            }.unwrap());
+        }
+        for event in events.iter() {
+            counter.observe_event(event);
+        }
+        assert_eq!(counter.seen(), 7);
+        assert_eq!(counter.running(), 2);
+        let true_count = counter.true_count(table.shoe());
+        assert!((true_count - 2.0 * 52.0 / table.shoe().remaining() as f64).abs() < 1e-9);
+        counter.observe_event(&Event::Shuffled);
+        assert_eq!(counter.running(), 0);
+    }
+
+    pub fn count_through_dry_shoe() {
+        // Round 1: player 10, 8 stands against 9, 8. Round 2 deals 10, 9, 5 and runs dry
+        // on the hole card, so the first round's discards come back into play.
+        let mut deck = Deck::new();
+        deck.deal(52);
+        deck.extend([10, 9, 8, 8, 10, 9, 5].iter().rev().map(|&v| Card::_test_card(v)).collect());
+        let mut table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(1, 100), 10, Shoe::stacked(deck));
+        let mut counter = Counter::new(CountSystem::HiLo, 1);
+        let mut events = Vec::new();
+        for _ in 0..2 {
+            events.extend(table.start_round().unwrap());
+            while table.phase() != Phase::RoundOver {
+                events.extend(match table.next_decision() {
+                    Some(Decision::Bet { seat }) => table.place_bet(seat, 10),
+                    Some(Decision::Action { .. }) => table.act(Action::Stand),
+                    Some(_) => table.insure(0, 0),
+                    None => table.advance(),
+                }.unwrap());
+            }
+        }
+        let refill = events.iter().position(|e| *e == Event::ShoeRefilled).unwrap();
+        assert_eq!(events[refill - 1], Event::PlayerCard { seat: 0, hand: 0, card: Card::_test_card(5), total: 15 });
+        assert_eq!(events[refill + 1], Event::DealerHoleCard);
+        for event in events.iter() {
+            counter.observe_event(event);
+        }
+        let cards: Vec<&Card> = events[refill..].iter().filter_map(|e| match e {
+            Event::PlayerCard { card, .. } | Event::DealerUpcard { card }
+            | Event::HoleCardRevealed { card, .. } | Event::DealerCard { card, .. } => Some(card),
+            _ => None,
+        }).collect();
+        assert_eq!(counter.seen(), cards.len());
+        assert_eq!(counter.running(), cards.iter().map(|&c| CountSystem::HiLo.tag(c)).sum::<i32>());
+    }
+}
@@ -1,9 +1,13 @@
-use std::fmt;
-use rand::thread_rng;
+use crate::error::{ Error, Result };
+use serde::{ Deserialize, Serialize };
+use std::{ fmt, mem };
+use std::str::FromStr;
+use rand::Rng;
 use rand::seq::SliceRandom;
 
-#[derive(Debug, PartialEq)]
-enum Value {
+/// A card's suit, carrying its numeric blackjack value (Aces count 11).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
     Spades(u8),
     Clubs(u8),
     Hearts(u8),
@@ -21,17 +25,28 @@ impl Value {
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// A playing card, displayed as e.g. "Ace of Spades". Saved files hold cards the same way.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct Card {
     value: Value,
     name: String,
 }
 
 impl Card {
-    fn new(value: Value, name: &str) -> Card {
+    /// Builds a card from its suit and value, and a name such as "Ace", "King" or "7".
+    pub fn new(value: Value, name: &str) -> Card {
         Card { value, name: String::from(name) }
     }
 
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn numeric_value(&self) -> u8 {
         match self.value {
             Value::Spades(n) => n,
@@ -41,6 +56,27 @@ impl Card {
         }
     }
 
+    /// The card's rank from 1 for an Ace to 13 for a King, telling the ten-value cards apart.
+    pub fn rank(&self) -> u8 {
+        match self.name.as_str() {
+            "Ace" => 1,
+            "Jack" => 11,
+            "Queen" => 12,
+            "King" => 13,
+            n => n.parse().unwrap_or(0),
+        }
+    }
+
+    pub fn same_suit(&self, other: &Card) -> bool {
+        mem::discriminant(&self.value) == mem::discriminant(&other.value)
+    }
+
+    /// Hearts and Diamonds are red, Spades and Clubs black.
+    pub fn is_red(&self) -> bool {
+        matches!(self.value, Value::Hearts(_) | Value::Diamonds(_))
+    }
+
+    #[cfg(test)]
     pub fn _test_hand(n: u8) -> Vec<Card> {
         match n {
             0 =>
@@ -77,6 +113,17 @@ impl Card {
         }
     }
 
+    #[cfg(test)]
+    pub fn _test_card(n: u8) -> Card {
+        let name = match n {
+            11 => String::from("Ace"),
+            10 => String::from("King"),
+            _ => format!("{n}"),
+        };
+        Card { value: Value::Spades(n), name }
+    }
+
+    #[cfg(test)]
     pub fn _last_card() -> Card {
         Card { value: Value::Clubs(11), name: String::from("Ace") }
     }
@@ -93,12 +140,52 @@ impl fmt::Display for Card {
     }
 }
 
+impl FromStr for Card {
+    type Err = Error;
+
+    /// Reads a card written the way it is displayed, such as "Ace of Spades".
+    fn from_str(s: &str) -> Result<Card> {
+        let invalid = || Error::Parse(format!("'{}' is not a card", s));
+        let (name, suite) = s.split_once(" of ").ok_or_else(invalid)?;
+        let value = match name {
+            "Ace" => 11,
+            "King" | "Queen" | "Jack" => 10,
+            n => match n.parse::<u8>() {
+                Ok(n) if (2..=10).contains(&n) => n,
+                _ => return Err(invalid()),
+            },
+        };
+        if !Deck::SUITES.contains(&suite) {
+            return Err(invalid());
+        }
+
+        Ok(Card::new(Value::match_enum(suite, value), name))
+    }
+}
+
+impl From<Card> for String {
+    fn from(card: Card) -> String {
+        card.to_string()
+    }
+}
+
+impl TryFrom<String> for Card {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Card> {
+        s.parse()
+    }
+}
+
+/// An ordered stack of cards, dealt from the top (the end of the stack).
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Deck(Vec<Card>);
 
 impl Deck {
     const SUITES: [&'static str; 4] = ["Spades", "Clubs", "Hearts", "Diamonds"];
     const FACE_CARDS: [&'static str; 4] = ["Ace", "King", "Queen", "Jack"];
 
+    /// A single unshuffled 52-card deck.
     pub fn new() -> Deck {
         let mut deck = Deck(Vec::new());
         for suite in Self::SUITES.iter() {
@@ -120,17 +207,40 @@ impl Deck {
         deck
     }
 
-    pub fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.0.shuffle(&mut rng);
+    /// Builds a single stack from several standard decks, one after the other.
+    pub fn with_decks(n: u8) -> Deck {
+        let mut deck = Deck(Vec::new());
+        for _ in 0..n {
+            deck.0.extend(Deck::new().0);
+        }
+
+        deck
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn extend(&mut self, cards: Vec<Card>) {
+        self.0.extend(cards);
+    }
+
+    /// Shuffles with `rng`, so a seeded generator gives the same order every time.
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.0.shuffle(rng);
+    }
+
+    /// Deals up to `n` cards, stopping early if the deck runs out.
     pub fn deal(&mut self, n: usize) -> Vec<Card> {
         let mut _vec = Vec::new();
         for _ in 0..n {
             match self.0.pop() {
                 Some(i) => _vec.push(i),
-                None => panic!("Program tried to deal from an empty Deck"),
+                None => break,
             }
         }
 
@@ -138,10 +248,19 @@ impl Deck {
     }
 }
 
+impl Default for Deck {
+    fn default() -> Deck {
+        Deck::new()
+    }
+}
+
 
+#[cfg(test)]
 #[allow(unused)]
 pub mod tests {
     use super::*;
+    use rand::{ thread_rng, SeedableRng };
+    use rand_chacha::ChaCha8Rng;
 
     pub fn create_card() {
         let first_card = Card::new(Value::Spades(5), "5");
@@ -151,6 +270,22 @@ pub mod tests {
         let second_card = Card::new(Value::Spades(11), "Ace");
         assert_eq!(format!("{second_card}"), "Ace of Spades");
         assert_ne!(first_card.value, second_card.value);
+        assert_eq!((first_card.rank(), second_card.rank()), (5, 1));
+        let queen: Card = "Queen of Hearts".parse().unwrap();
+        assert_eq!(queen.rank(), 12);
+        assert!(queen.is_red() && !second_card.is_red());
+        assert!(first_card.same_suit(&second_card) && !queen.same_suit(&second_card));
+        assert_eq!(Deck::new().deal(52).iter().map(|c| c.rank() as u32).sum::<u32>(), 4 * 91);
+    }
+
+    pub fn parse_cards() {
+        for card in Deck::new().deal(52) {
+            assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+        }
+        assert_eq!("Queen of Hearts".parse::<Card>().unwrap().numeric_value(), 10);
+        assert!("1 of Spades".parse::<Card>().is_err());
+        assert!("Ace of Cups".parse::<Card>().is_err());
+        assert!("Ace".parse::<Card>().is_err());
     }
 
     pub fn create_deck() {
@@ -164,16 +299,27 @@ pub mod tests {
 
     pub fn shuffle_deck() {
         let mut deck = Deck::new();
-        deck.shuffle();
+        deck.shuffle(&mut thread_rng());
         let iter = deck.0.iter()
             .take(13)
             .fold(true, |acc, curr| {
                 match curr.value {
-                    Value::Spades(_) => if acc { true } else { false }
+                    Value::Spades(_) => acc,
                     _ => false
                 }
             });
-        assert_ne!(iter, true);
+        assert!(!iter);
+    }
+
+    pub fn seeded_shuffle() {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut ChaCha8Rng::seed_from_u64(42));
+        let names: Vec<String> = deck.deal(3).iter().map(|c| c.to_string()).collect();
+        assert_eq!(names, vec!["3 of Spades", "6 of Hearts", "7 of Spades"]);
+        let mut again = Deck::new();
+        again.shuffle(&mut ChaCha8Rng::seed_from_u64(42));
+        again.deal(3);
+        assert_eq!(again.0, deck.0);
     }
 
     pub fn deal_from_deck() {
@@ -186,5 +332,15 @@ pub mod tests {
         ]);
         assert_eq!(deck.0.len(), 49);
         assert_ne!(dealt_cards[2], deck.0[48]);
+        assert_eq!(deck.deal(60).len(), 49);
+        assert!(deck.is_empty());
+        assert!(deck.deal(1).is_empty());
+    }
+
+    pub fn combine_decks() {
+        let deck = Deck::with_decks(6);
+        assert_eq!(deck.len(), 312);
+        assert_eq!(deck.0.iter().filter(|c| c.value == Value::Hearts(11)).count(), 6);
+        assert_eq!(deck.0[52], deck.0[0]);
     }
 }
@@ -0,0 +1,83 @@
+use std::{ error, fmt };
+
+/// Everything that can go wrong when driving the game through the library.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// A bet, double, split or insurance stake was larger than the chips left.
+    InsufficientChips,
+    /// The dealer was asked to bet or settle a wager.
+    DealerBet,
+    /// A bet was resolved on a hand with nothing in the pot.
+    NoBet,
+    /// A hand that is not a pair was split.
+    NotAPair,
+    /// Insurance was staked beyond half the original bet.
+    InsuranceTooLarge,
+    /// Insurance was resolved when none had been taken.
+    NoInsurance,
+    /// A side bet was placed that the table does not offer.
+    SideBetNotOffered,
+    /// A side bet was staked beyond the main bet.
+    SideBetTooLarge,
+    /// The table was asked to do something that does not belong to the current phase.
+    WrongPhase,
+    /// A decision was made for a seat that is not being asked for one.
+    NotYourTurn,
+    /// An action was taken that is not among the hand's valid moves.
+    IllegalAction,
+    /// A bet was below the table minimum while the player could afford it.
+    BetBelowMinimum,
+    /// A round was started with every player out of chips.
+    NoPlayersLeft,
+    /// A card was needed with every card of the shoe already on the table.
+    ShoeEmpty,
+    /// A table rule set failed validation.
+    InvalidRules(String),
+    /// Simulation options asked for something that cannot be run.
+    InvalidOptions(String),
+    /// A profile could not be made with the name given.
+    InvalidProfile(String),
+    /// A file could not be parsed.
+    Parse(String),
+    /// A file could not be read or written.
+    Io(String),
+    /// A hand history round played out differently when dealt again under its rules.
+    HistoryMismatch { round: u32, recorded: String, replayed: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InsufficientChips => write!(f, "Program tried to bet more chips than it has"),
+            Error::DealerBet => write!(f, "Program tried to bet as a dealer"),
+            Error::NoBet => write!(f, "Tried to resolve when no bet was made"),
+            Error::NotAPair => write!(f, "Tried to split a hand that is not a pair"),
+            Error::InsuranceTooLarge => write!(f, "Insurance cannot be more than half the original bet"),
+            Error::NoInsurance => write!(f, "Tried to resolve insurance when none was taken"),
+            Error::SideBetNotOffered => write!(f, "The table does not offer that side bet"),
+            Error::SideBetTooLarge => write!(f, "A side bet cannot be more than the main bet"),
+            Error::WrongPhase => write!(f, "That is not possible in the current phase of the round"),
+            Error::NotYourTurn => write!(f, "That seat is not the one being asked for a decision"),
+            Error::IllegalAction => write!(f, "That action is not allowed for this hand"),
+            Error::BetBelowMinimum => write!(f, "Bet is below the table minimum"),
+            Error::NoPlayersLeft => write!(f, "Every player is out of chips"),
+            Error::ShoeEmpty => write!(f, "Every card in the shoe is on the table"),
+            Error::InvalidRules(reason) => write!(f, "Invalid table rules: {}", reason),
+            Error::InvalidOptions(reason) => write!(f, "Invalid simulation options: {}", reason),
+            Error::InvalidProfile(reason) => write!(f, "Invalid profile: {}", reason),
+            Error::Parse(reason) => write!(f, "Could not parse file: {}", reason),
+            Error::Io(reason) => write!(f, "{}", reason),
+            Error::HistoryMismatch { round, recorded, replayed } => write!(
+                f,
+                "Round {} does not follow the rules: the history has '{}' where the table gives '{}'",
+                round,
+                recorded,
+                replayed,
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,239 @@
+use crate::deck::Card;
+use crate::hand::Hand;
+use crate::player::Action;
+use crate::rules::{ HoleCardRule, TableRules };
+use std::collections::HashMap;
+
+/// Every card value with its chance of being drawn from an infinite deck.
+const DRAWS: [(u8, f64); 10] = [
+    (2, 1.0 / 13.0),
+    (3, 1.0 / 13.0),
+    (4, 1.0 / 13.0),
+    (5, 1.0 / 13.0),
+    (6, 1.0 / 13.0),
+    (7, 1.0 / 13.0),
+    (8, 1.0 / 13.0),
+    (9, 1.0 / 13.0),
+    (10, 4.0 / 13.0),
+    (11, 1.0 / 13.0),
+];
+
+/// The best total of a hand holding `hard` with every Ace as 1, and whether it is soft.
+fn best_total(hard: u8, ace: bool) -> (u8, bool) {
+    match ace && hard <= 11 {
+        true => (hard + 10, true),
+        false => (hard, false),
+    }
+}
+
+/// How the dealer's hand finishes from a given upcard. When the dealer peeks, a hand
+/// that reaches play is known not to be a blackjack, so those odds are left out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DealerOdds {
+    /// The chance of finishing on 17, 18, 19, 20 and 21.
+    pub totals: [f64; 5],
+    pub bust: f64,
+    pub blackjack: f64,
+}
+
+impl DealerOdds {
+    pub fn new(upcard: &Card, rules: &TableRules) -> DealerOdds {
+        let mut odds = DealerOdds { totals: [0.0; 5], bust: 0.0, blackjack: 0.0 };
+        let up = upcard.numeric_value();
+        odds.draw(if up == 11 { 1 } else { up }, up == 11, 1, 1.0, rules);
+        if rules.hole_card.dealer_peeks(upcard) {
+            let not_blackjack = 1.0 - odds.blackjack;
+            odds.totals.iter_mut().for_each(|p| *p /= not_blackjack);
+            odds.bust /= not_blackjack;
+            odds.blackjack = 0.0;
+        }
+
+        odds
+    }
+
+    fn draw(&mut self, hard: u8, ace: bool, cards: u8, p: f64, rules: &TableRules) {
+        let (total, soft) = best_total(hard, ace);
+        if cards == 2 && total == 21 {
+            self.blackjack += p;
+        } else if hard > 21 {
+            self.bust += p;
+        } else if total < 17 || (rules.dealer_hits_soft_17 && total == 17 && soft) {
+            for (value, q) in DRAWS {
+                let card = if value == 11 { 1 } else { value };
+                self.draw(hard + card, ace || value == 11, cards + 1, p * q, rules);
+            }
+        } else {
+            self.totals[(total - 17) as usize] += p;
+        }
+    }
+}
+
+/// Expected values, in units of the hand's bet, for a hand played against one upcard.
+struct Calculator<'a> {
+    rules: &'a TableRules,
+    dealer: DealerOdds,
+    /// The bet lost to a dealer blackjack, per unit staked on the original hand.
+    original: f64,
+    memo: HashMap<(u8, bool), f64>,
+}
+
+impl<'a> Calculator<'a> {
+    fn new(dealer: DealerOdds, rules: &'a TableRules, original: f64) -> Calculator<'a> {
+        Calculator { rules, dealer, original, memo: HashMap::new() }
+    }
+
+    /// What a dealer blackjack takes from a hand with `stake` on it: everything when
+    /// the dealer never peeks, the original bet only otherwise.
+    fn blackjack_loss(&self, stake: f64) -> f64 {
+        match self.rules.hole_card {
+            HoleCardRule::NoPeek => stake,
+            _ => self.original,
+        }
+    }
+
+    fn stand(&self, total: u8, stake: f64) -> f64 {
+        if total > 21 {
+            return -stake;
+        }
+        let mut win = self.dealer.bust;
+        let mut lose = 0.0;
+        for (n, p) in self.dealer.totals.iter().enumerate() {
+            match 17 + n as u8 {
+                t if t < total => win += p,
+                t if t > total => lose += p,
+                _ => (),
+            }
+        }
+
+        stake * (win - lose) - self.dealer.blackjack * self.blackjack_loss(stake)
+    }
+
+    /// The value of hitting, then standing or hitting again as the totals suggest.
+    fn hit(&mut self, hard: u8, ace: bool) -> f64 {
+        DRAWS.iter()
+            .map(|&(value, q)| {
+                let hard = hard + if value == 11 { 1 } else { value };
+                match hard > 21 {
+                    true => -q,
+                    false => q * self.stand_or_hit(hard, ace || value == 11),
+                }
+            })
+            .sum()
+    }
+
+    fn stand_or_hit(&mut self, hard: u8, ace: bool) -> f64 {
+        if let Some(&ev) = self.memo.get(&(hard, ace)) {
+            return ev;
+        }
+        let stand = self.stand(best_total(hard, ace).0, 1.0);
+        let ev = match hard >= 21 {
+            true => stand,
+            false => stand.max(self.hit(hard, ace)),
+        };
+        self.memo.insert((hard, ace), ev);
+
+        ev
+    }
+
+    fn double(&self, hard: u8, ace: bool) -> f64 {
+        DRAWS.iter()
+            .map(|&(value, q)| {
+                let hard = hard + if value == 11 { 1 } else { value };
+                q * self.stand(best_total(hard, ace || value == 11).0, 2.0)
+            })
+            .sum()
+    }
+
+    /// Two hands, each starting on one card of the pair and drawing a second. Split aces
+    /// stand on two cards; other hands may double when the rules allow. No re-splits.
+    fn split(&self, value: u8) -> f64 {
+        let mut hand = Calculator::new(self.dealer, self.rules, self.original / 2.0);
+        let start = if value == 11 { 1 } else { value };
+        let ev: f64 = DRAWS.iter()
+            .map(|&(second, q)| {
+                let hard = start + if second == 11 { 1 } else { second };
+                let ace = value == 11 || second == 11;
+                let (total, _) = best_total(hard, ace);
+                if value == 11 {
+                    return q * hand.stand(total, 1.0);
+                }
+                let mut best = hand.stand_or_hit(hard, ace);
+                if self.rules.double_after_split && self.rules.double.allows(total) {
+                    best = best.max(hand.double(hard, ace));
+                }
+                q * best
+            })
+            .sum();
+
+        2.0 * ev
+    }
+}
+
+/// The expected value of each of `valid_moves` on `hand` against `upcard`, in units of
+/// the hand's bet. Cards are drawn from an infinite deck, later decisions are played
+/// as hit or stand only, and split hands are not re-split.
+pub fn action_evs(hand: &Hand, upcard: &Card, rules: &TableRules, valid_moves: &[Action]) -> Vec<(Action, f64)> {
+    let original = if hand.is_split() { 0.5 } else { 1.0 };
+    let mut calculator = Calculator::new(DealerOdds::new(upcard, rules), rules, original);
+    let hard = hand.hard_total();
+    let ace = hand.cards.iter().any(|c| c.numeric_value() == 11);
+    valid_moves.iter()
+        .map(|action| {
+            let ev = match action {
+                Action::Stand => calculator.stand(hand.total(), 1.0),
+                Action::Hit => calculator.hit(hard, ace),
+                Action::DoubleDown => calculator.double(hard, ace),
+                Action::Split => calculator.split(hand.cards[0].numeric_value()),
+                Action::Surrender => -0.5,
+            };
+            (action.clone(), ev)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+
+    const ALL: [Action; 5] = [Action::Hit, Action::Stand, Action::Surrender, Action::DoubleDown, Action::Split];
+
+    fn ev_of(evs: &[(Action, f64)], action: Action) -> f64 {
+        evs.iter().find(|(a, _)| *a == action).unwrap().1
+    }
+
+    pub fn dealer_odds() {
+        let no_peek = TableRules { hole_card: HoleCardRule::NoPeek, ..TableRules::vegas_strip() };
+        for up in 2..=11 {
+            let odds = DealerOdds::new(&Card::_test_card(up), &no_peek);
+            let sum = odds.totals.iter().sum::<f64>() + odds.bust + odds.blackjack;
+            assert!((sum - 1.0).abs() < 1e-9);
+        }
+        let six = DealerOdds::new(&Card::_test_card(6), &TableRules::vegas_strip());
+        assert!((six.bust - 0.4232).abs() < 0.0005);
+        let ace = DealerOdds::new(&Card::_test_card(11), &no_peek);
+        assert!((ace.blackjack - 4.0 / 13.0).abs() < 1e-9);
+        let peeked = DealerOdds::new(&Card::_test_card(11), &TableRules::vegas_strip());
+        assert_eq!(peeked.blackjack, 0.0);
+        let h17 = DealerOdds::new(&Card::_test_card(6), &TableRules::downtown_vegas());
+        assert!(h17.bust > six.bust);
+    }
+
+    pub fn compare_action_evs() {
+        let rules = TableRules::vegas_strip();
+        let evs = action_evs(&Hand::_test_hand(vec![10, 6]), &Card::_test_card(10), &rules, &ALL[..4]);
+        assert!(ev_of(&evs, Action::Surrender) > ev_of(&evs, Action::Hit));
+        assert!(ev_of(&evs, Action::Hit) > ev_of(&evs, Action::DoubleDown));
+        assert!((ev_of(&evs, Action::Stand) + 0.540).abs() < 0.005);
+        let evs = action_evs(&Hand::_test_hand(vec![6, 5]), &Card::_test_card(6), &rules, &ALL[..4]);
+        assert!(ev_of(&evs, Action::DoubleDown) > ev_of(&evs, Action::Hit));
+        assert!(ev_of(&evs, Action::Hit) > ev_of(&evs, Action::Stand));
+        let evs = action_evs(&Hand::_test_hand(vec![8, 8]), &Card::_test_card(6), &rules, &ALL);
+        assert!(ev_of(&evs, Action::Split) > ev_of(&evs, Action::Stand));
+        assert!(ev_of(&evs, Action::Split) > 0.0);
+        let evs = action_evs(&Hand::_test_hand(vec![10, 10]), &Card::_test_card(10), &rules, &ALL[..2]);
+        assert!(ev_of(&evs, Action::Stand) > 0.5);
+        assert!(ev_of(&evs, Action::Hit) < -0.8);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::deck::Card;
+use std::fmt;
+
+/// The cards and bet of one hand; a player owns several after splitting.
+#[derive(Clone, Debug)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub(crate) pot: u32,
+    pub(crate) split: bool,
+    pub(crate) doubled: bool,
+}
+
+impl Hand {
+    pub fn new() -> Hand {
+        Hand { cards: Vec::new(), pot: 0, split: false, doubled: false }
+    }
+
+    /// Builds a hand from `cards` with nothing bet on it.
+    pub fn from_cards(cards: Vec<Card>) -> Hand {
+        Hand { cards, ..Hand::new() }
+    }
+
+    fn has_ace(&self) -> bool {
+        self.cards.iter().any(|c| c.numeric_value() == 11)
+    }
+
+    /// The total counting every Ace as 1.
+    pub fn hard_total(&self) -> u8 {
+        self.cards.iter()
+            .map(|c| match c.numeric_value() {
+                11 => 1,
+                n => n,
+            })
+            .sum()
+    }
+
+    /// The total with one Ace counted as 11, if that does not bust the hand.
+    /// Only one Ace can ever count as 11, as two would make at least 22.
+    pub fn soft_total(&self) -> Option<u8> {
+        let hard_total = self.hard_total();
+        match self.has_ace() && hard_total <= 11 {
+            true => Some(hard_total + 10),
+            false => None,
+        }
+    }
+
+    /// The best total: soft when that does not bust, hard otherwise.
+    pub fn total(&self) -> u8 {
+        self.soft_total().unwrap_or_else(|| self.hard_total())
+    }
+
+    /// True while an Ace in the hand is still counted as 11.
+    pub fn is_soft(&self) -> bool {
+        self.soft_total().is_some()
+    }
+
+    pub fn is_busted(&self) -> bool {
+        self.hard_total() > 21
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.cards.len() == 2 && self.cards[0].numeric_value() == self.cards[1].numeric_value()
+    }
+
+    /// Two cards making 21 on an unsplit hand: a blackjack.
+    pub fn is_natural(&self) -> bool {
+        self.cards.len() == 2 && !self.split && self.total() == 21
+    }
+
+    pub fn pot(&self) -> u32 {
+        self.pot
+    }
+
+    pub fn is_doubled(&self) -> bool {
+        self.doubled
+    }
+
+    pub fn is_split(&self) -> bool {
+        self.split
+    }
+
+    /// Split aces receive one card each and cannot be played any further.
+    pub fn is_split_aces(&self) -> bool {
+        self.split && self.cards[0].numeric_value() == 11
+    }
+
+    #[cfg(test)]
+    pub fn _test_hand(values: Vec<u8>) -> Hand {
+        Hand::from_cards(values.into_iter().map(Card::_test_card).collect())
+    }
+}
+
+impl Default for Hand {
+    fn default() -> Self {
+        Hand::new()
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cards = self.cards.iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        match self.is_soft() && self.total() < 21 {
+            true => write!(f, "{} (total: soft {})", cards, self.total()),
+            false => write!(f, "{} (total: {})", cards, self.total()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+
+    /// Every multiset of `len` card values from 2 to 11, in non-decreasing order.
+    fn combinations(len: usize) -> Vec<Vec<u8>> {
+        let mut combinations = vec![Vec::new()];
+        for _ in 0..len {
+            combinations = combinations.into_iter()
+                .flat_map(|c: Vec<u8>| {
+                    let lowest = *c.last().unwrap_or(&2);
+                    (lowest..=11).map(move |v| {
+                        let mut next = c.clone();
+                        next.push(v);
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+    }
+
+    /// The best total found by trying every way of counting the Aces.
+    fn best_total(values: &[u8]) -> (u8, bool) {
+        let aces = values.iter().filter(|&&v| v == 11).count() as u8;
+        let hard: u8 = values.iter().map(|&v| if v == 11 { 1 } else { v }).sum();
+        let best = (0..=aces)
+            .map(|elevens| (hard + elevens * 10, elevens > 0))
+            .filter(|&(total, _)| total <= 21)
+            .max_by_key(|&(total, _)| total);
+
+        best.unwrap_or((hard, false))
+    }
+
+    pub fn ace_nine_ace() {
+        let hand = Hand::_test_hand(vec![11, 9, 11]);
+        assert_eq!(hand.hard_total(), 11);
+        assert_eq!(hand.soft_total(), Some(21));
+        assert_eq!(hand.total(), 21);
+        assert!(hand.is_soft());
+        assert!(!hand.is_natural());
+        assert_eq!(format!("{}", Hand::_test_hand(vec![11, 6])), "Ace of Spades, 6 of Spades (total: soft 17)");
+        assert_eq!(format!("{}", Hand::_test_hand(vec![11, 10])), "Ace of Spades, King of Spades (total: 21)");
+    }
+
+    pub fn hand_properties() {
+        for len in 1..=11 {
+            for values in combinations(len) {
+                let hand = Hand::_test_hand(values.clone());
+                let (total, soft) = best_total(&values);
+                assert_eq!(hand.total(), total, "{:?}", values);
+                assert_eq!(hand.is_soft(), soft, "{:?}", values);
+                assert_eq!(hand.is_busted(), total > 21, "{:?}", values);
+                assert!(hand.hard_total() <= hand.total(), "{:?}", values);
+                assert_eq!(hand.is_natural(), len == 2 && total == 21, "{:?}", values);
+                assert_eq!(hand.is_pair(), len == 2 && values[0] == values[1], "{:?}", values);
+            }
+        }
+    }
+}
@@ -0,0 +1,853 @@
+//! Hand histories: a plain text record of every round played in a session.
+//!
+//! A history starts with a header of `Key: value` lines, the table rules as indented
+//! TOML and a blank line:
+//!
+//! ```text
+//! Blackjack hand history, format 4
+//! Session: 1760788800-3f2a
+//! Started: 2025-10-18 12:00:00 UTC (1760788800)
+//! Seed: 99
+//! Players: 2
+//! Starting chips: 100, 250
+//! Minimum bet: 10
+//! Table rules: Vegas Strip: 6 decks, S17, blackjack pays 3:2, double any two, DAS, late surrender, split to 4 hands, peek
+//!     name = "Vegas Strip"
+//!     decks = 6
+//!     ...
+//! ```
+//!
+//! Each round follows as a block of lines ending in a blank line. The block opens with the
+//! round number and time, then any shuffle and the shoe position before the deal, then
+//! one line for each [`Event`] in order, and closes with every player's chips:
+//!
+//! ```text
+//! *** Round 1 *** 2025-10-18 12:00:05 UTC (1760788805)
+//! Shoe: 311 cards left, 1 discarded
+//! Player 1 bets 10
+//! Player 1 hand 1 gets 10 of Spades (total: 10)
+//! Dealer shows King of Spades
+//! Player 1 hand 1 gets Jack of Spades (total: 20)
+//! Dealer takes a hole card
+//! Dealer checks the hole card: no blackjack
+//! Player 1 hand 1 to act
+//! Player 1 hand 1 chooses to Stand
+//! Dealer reveals 10 of Diamonds (total: 20)
+//! Dealer stands on 20
+//! Player 1 hand 1 is paid 10 for a stand-off
+//! Chips: Player 1 100
+//! ```
+//!
+//! Players and hands are numbered from 1, cards are written the way [`Card`] displays
+//! them, and chip amounts are whole chips. Payouts include the returned stake, so a lost
+//! hand is paid 0. The format only changes under a new format number, and older
+//! histories are still read: format 2 gave each seat its own starting chips where
+//! format 1 has one count for every seat, format 3 added the side bet lines and format 4
+//! the line for a shoe refilled from its discards mid-round.
+
+use crate::deck::{ Card, Deck };
+use crate::error::{ Error, Result };
+use crate::input::{ self, PlayerInput };
+use crate::player::{ Action, BetResult, PlayerList };
+use crate::progress::{ format_timestamp, timestamp };
+use crate::rules::{ BlackjackPayout, TableRules };
+use crate::shoe::Shoe;
+use crate::side_bet::{ SideBetKind, SideBetOutcome };
+use crate::table::{ Event, Phase, Table };
+use rand::Rng;
+use std::collections::VecDeque;
+use std::fs::{ self, File };
+use std::io::{ BufWriter, Write };
+use std::path::Path;
+use std::str::FromStr;
+
+/// Every hand history opens with this and its format number. This version writes the
+/// latest format and reads any earlier one.
+const FORMAT_LINE: &str = "Blackjack hand history, format ";
+const FORMAT: u32 = 4;
+const RULES_INDENT: &str = "    ";
+
+/// Everything about a session that stays the same from round to round.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub session: String,
+    /// When the session started, in seconds since the Unix epoch.
+    pub started: u64,
+    pub seed: u64,
+    pub players: usize,
+    /// Every player's chips before the first round, by seat.
+    pub starting_chips: Vec<u32>,
+    pub min_bet: u32,
+    pub rules: TableRules,
+}
+
+impl Header {
+    /// A header for a session starting now, with a new session id.
+    pub fn new(seed: u64, starting_chips: Vec<u32>, min_bet: u32, rules: TableRules) -> Header {
+        let started = timestamp();
+        let session = format!("{}-{:04x}", started, rand::thread_rng().gen::<u16>());
+        let players = starting_chips.len();
+
+        Header { session, started, seed, players, starting_chips, min_bet, rules }
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "{}{}", FORMAT_LINE, FORMAT)?;
+        writeln!(out, "Session: {}", self.session)?;
+        writeln!(out, "Started: {} ({})", format_timestamp(self.started), self.started)?;
+        writeln!(out, "Seed: {}", self.seed)?;
+        writeln!(out, "Players: {}", self.players)?;
+        let chips: Vec<String> = self.starting_chips.iter().map(|c| c.to_string()).collect();
+        writeln!(out, "Starting chips: {}", chips.join(", "))?;
+        writeln!(out, "Minimum bet: {}", self.min_bet)?;
+        writeln!(out, "Table rules: {}", self.rules)?;
+        for line in self.rules.to_toml().lines() {
+            writeln!(out, "{}{}", RULES_INDENT, line)?;
+        }
+        writeln!(out)
+    }
+
+    fn parse(lines: &[&str]) -> Result<Header> {
+        let format = lines.first()
+            .and_then(|line| line.strip_prefix(FORMAT_LINE)?.parse::<u32>().ok())
+            .filter(|format| (1..=FORMAT).contains(format))
+            .ok_or_else(|| Error::Parse(String::from("not a hand history, or written in a newer format")))?;
+        let value = |key: &str| {
+            lines.iter()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix(": "))
+                .ok_or_else(|| Error::Parse(format!("the header has no {}", key)))
+        };
+        let rules: Vec<&str> = lines.iter().filter_map(|line| line.strip_prefix(RULES_INDENT)).collect();
+        let started = value("Started")?;
+        let started = started.rsplit_once('(').and_then(|(_, s)| s.strip_suffix(')')).unwrap_or(started);
+        let players = number(value("Players")?)?;
+        let mut starting_chips = value("Starting chips")?.split(", ").map(number).collect::<Result<Vec<u32>>>()?;
+        // Format 1 gives one count that every seat started with.
+        if let (1, [chips]) = (format, &starting_chips[..]) {
+            starting_chips = vec![*chips; players];
+        }
+        if starting_chips.len() != players {
+            return Err(Error::Parse(format!("the header has starting chips for {} of {} players", starting_chips.len(), players)));
+        }
+
+        Ok(Header {
+            session: value("Session")?.to_string(),
+            started: number(started)?,
+            seed: number(value("Seed")?)?,
+            players,
+            starting_chips,
+            min_bet: number(value("Minimum bet")?)?,
+            rules: TableRules::from_toml(&rules.join("\n"))?,
+        })
+    }
+}
+
+fn number<T: FromStr>(s: &str) -> Result<T> {
+    s.trim().parse().map_err(|_| Error::Parse(format!("'{}' is not a number", s)))
+}
+
+fn player_hand(seat: usize, hand: usize) -> String {
+    format!("Player {} hand {}", seat + 1, hand + 1)
+}
+
+fn result_name(result: &BetResult) -> &'static str {
+    match result {
+        BetResult::Win => "a win",
+        BetResult::Lose => "a loss",
+        BetResult::Surrender => "a surrender",
+        BetResult::StandOff => "a stand-off",
+        BetResult::Blackjack(BlackjackPayout::ThreeToTwo) => "a blackjack at 3:2",
+        BetResult::Blackjack(BlackjackPayout::SixToFive) => "a blackjack at 6:5",
+        BetResult::Blackjack(BlackjackPayout::EvenMoney) => "a blackjack at 1:1",
+        BetResult::EvenMoney => "even money",
+    }
+}
+
+/// The history line for `event`. Round starts and ends are written by [`HistoryWriter`]
+/// with the round's time and chip counts instead.
+pub fn event_line(event: &Event) -> Option<String> {
+    let line = match event {
+        Event::RoundStarted { .. } | Event::RoundEnded => return None,
+        Event::Shuffled => String::from("Shoe shuffled"),
+        Event::ShoeRefilled => String::from("Shoe refilled from the discards"),
+        Event::BetPlaced { seat, amount } => format!("Player {} bets {}", seat + 1, amount),
+        Event::SideBetPlaced { seat, kind, amount } => format!("Player {} bets {} on {}", seat + 1, amount, kind),
+        Event::PlayerCard { seat, hand, card, total } => format!("{} gets {} (total: {})", player_hand(*seat, *hand), card, total),
+        Event::DealerUpcard { card } => format!("Dealer shows {}", card),
+        Event::DealerHoleCard => String::from("Dealer takes a hole card"),
+        Event::SideBetSettled { seat, kind, outcome: Some(outcome), payout } => {
+            format!("Player {} is paid {} on {} ({})", seat + 1, payout, kind, outcome)
+        },
+        Event::SideBetSettled { seat, kind, outcome: None, .. } => format!("Player {} loses on {}", seat + 1, kind),
+        Event::InsuranceOpen => String::from("Insurance is open"),
+        Event::InsuranceTaken { seat, amount } => format!("Player {} takes {} chips of insurance", seat + 1, amount),
+        Event::EvenMoneyPaid { seat, payout } => format!("Player {} takes even money for {}", seat + 1, payout),
+        Event::Surrendered { seat, hand, refund } => format!("{} surrenders for {}", player_hand(*seat, *hand), refund),
+        Event::DealerPeeked { blackjack: true } => String::from("Dealer checks the hole card: blackjack"),
+        Event::DealerPeeked { blackjack: false } => String::from("Dealer checks the hole card: no blackjack"),
+        Event::InsuranceSettled { seat, payout } => format!("Player {} insurance pays {}", seat + 1, payout),
+        Event::TurnStarted { seat, hand } => format!("{} to act", player_hand(*seat, *hand)),
+        Event::ActionTaken { seat, hand, action } => format!("{} chooses to {}", player_hand(*seat, *hand), action),
+        Event::HandBusted { seat, hand, total } => format!("{} busts with {}", player_hand(*seat, *hand), total),
+        Event::Charlie { seat, hand } => format!("{} makes a charlie", player_hand(*seat, *hand)),
+        Event::HoleCardRevealed { card, total } => format!("Dealer reveals {} (total: {})", card, total),
+        Event::DealerCard { card, total } => format!("Dealer gets {} (total: {})", card, total),
+        Event::DealerStands { total } => format!("Dealer stands on {}", total),
+        Event::DealerBusts { total } => format!("Dealer busts with {}", total),
+        Event::HandSettled { seat, hand, result, payout } => {
+            format!("{} is paid {} for {}", player_hand(*seat, *hand), payout, result_name(result))
+        },
+        Event::OriginalBetsOnly { seat, refund } => format!("Player {} loses original bets only, refunded {}", seat + 1, refund),
+    };
+
+    Some(line)
+}
+
+/// Splits "Player N" or "Player N hand M" off the front of `line`, returning the
+/// zero-based seat, the hand if there was one and the rest of the line.
+fn split_player(line: &str) -> Result<(usize, Option<usize>, &str)> {
+    let rest = line.strip_prefix("Player ").ok_or_else(|| unknown(line))?;
+    let (seat, rest) = rest.split_once(' ').ok_or_else(|| unknown(line))?;
+    let seat = number::<usize>(seat)?.checked_sub(1).ok_or_else(|| unknown(line))?;
+    let Some(rest) = rest.strip_prefix("hand ") else {
+        return Ok((seat, None, rest));
+    };
+    let (hand, rest) = rest.split_once(' ').ok_or_else(|| unknown(line))?;
+    let hand = number::<usize>(hand)?.checked_sub(1).ok_or_else(|| unknown(line))?;
+
+    Ok((seat, Some(hand), rest))
+}
+
+fn unknown(line: &str) -> Error {
+    Error::Parse(format!("unrecognised hand history line '{}'", line))
+}
+
+fn side_bet_kind(s: &str) -> Result<SideBetKind> {
+    SideBetKind::ALL.into_iter().find(|kind| kind.to_string() == s).ok_or_else(|| unknown(s))
+}
+
+/// Reads "Ace of Spades (total: 21)".
+fn card_and_total(s: &str) -> Result<(Card, u8)> {
+    let (card, total) = s.split_once(" (total: ").ok_or_else(|| unknown(s))?;
+    let total = total.strip_suffix(')').ok_or_else(|| unknown(s))?;
+
+    Ok((card.parse()?, number(total)?))
+}
+
+/// Reads a line written by [`event_line`] back into its event.
+pub fn parse_event(line: &str) -> Result<Event> {
+    let event = match line {
+        "Shoe shuffled" => Event::Shuffled,
+        "Shoe refilled from the discards" => Event::ShoeRefilled,
+        "Dealer takes a hole card" => Event::DealerHoleCard,
+        "Insurance is open" => Event::InsuranceOpen,
+        "Dealer checks the hole card: blackjack" => Event::DealerPeeked { blackjack: true },
+        "Dealer checks the hole card: no blackjack" => Event::DealerPeeked { blackjack: false },
+        _ if line.starts_with("Dealer ") => {
+            let rest = &line["Dealer ".len()..];
+            if let Some(card) = rest.strip_prefix("shows ") {
+                Event::DealerUpcard { card: card.parse()? }
+            } else if let Some(rest) = rest.strip_prefix("reveals ") {
+                let (card, total) = card_and_total(rest)?;
+                Event::HoleCardRevealed { card, total }
+            } else if let Some(rest) = rest.strip_prefix("gets ") {
+                let (card, total) = card_and_total(rest)?;
+                Event::DealerCard { card, total }
+            } else if let Some(total) = rest.strip_prefix("stands on ") {
+                Event::DealerStands { total: number(total)? }
+            } else if let Some(total) = rest.strip_prefix("busts with ") {
+                Event::DealerBusts { total: number(total)? }
+            } else {
+                return Err(unknown(line));
+            }
+        },
+        _ => match split_player(line)? {
+            (seat, None, rest) => {
+                if let Some((amount, kind)) = rest.strip_prefix("bets ").and_then(|r| r.split_once(" on ")) {
+                    Event::SideBetPlaced { seat, kind: side_bet_kind(kind)?, amount: number(amount)? }
+                } else if let Some(amount) = rest.strip_prefix("bets ") {
+                    Event::BetPlaced { seat, amount: number(amount)? }
+                } else if let Some(rest) = rest.strip_prefix("is paid ") {
+                    let (payout, rest) = rest.split_once(" on ").ok_or_else(|| unknown(line))?;
+                    let (kind, outcome) = rest.strip_suffix(')').and_then(|r| r.split_once(" (")).ok_or_else(|| unknown(line))?;
+                    let outcome = SideBetOutcome::ALL.into_iter()
+                        .find(|o| o.to_string() == outcome)
+                        .ok_or_else(|| unknown(line))?;
+                    Event::SideBetSettled { seat, kind: side_bet_kind(kind)?, outcome: Some(outcome), payout: number(payout)? }
+                } else if let Some(kind) = rest.strip_prefix("loses on ") {
+                    Event::SideBetSettled { seat, kind: side_bet_kind(kind)?, outcome: None, payout: 0 }
+                } else if let Some(amount) = rest.strip_prefix("takes ").and_then(|r| r.strip_suffix(" chips of insurance")) {
+                    Event::InsuranceTaken { seat, amount: number(amount)? }
+                } else if let Some(payout) = rest.strip_prefix("takes even money for ") {
+                    Event::EvenMoneyPaid { seat, payout: number(payout)? }
+                } else if let Some(payout) = rest.strip_prefix("insurance pays ") {
+                    Event::InsuranceSettled { seat, payout: number(payout)? }
+                } else if let Some(refund) = rest.strip_prefix("loses original bets only, refunded ") {
+                    Event::OriginalBetsOnly { seat, refund: number(refund)? }
+                } else {
+                    return Err(unknown(line));
+                }
+            },
+            (seat, Some(hand), rest) => {
+                if let Some(rest) = rest.strip_prefix("gets ") {
+                    let (card, total) = card_and_total(rest)?;
+                    Event::PlayerCard { seat, hand, card, total }
+                } else if let Some(refund) = rest.strip_prefix("surrenders for ") {
+                    Event::Surrendered { seat, hand, refund: number(refund)? }
+                } else if rest == "to act" {
+                    Event::TurnStarted { seat, hand }
+                } else if let Some(action) = rest.strip_prefix("chooses to ") {
+                    let action = [Action::Hit, Action::Stand, Action::Surrender, Action::DoubleDown, Action::Split]
+                        .into_iter()
+                        .find(|a| a.to_string() == action)
+                        .ok_or_else(|| unknown(line))?;
+                    Event::ActionTaken { seat, hand, action }
+                } else if let Some(total) = rest.strip_prefix("busts with ") {
+                    Event::HandBusted { seat, hand, total: number(total)? }
+                } else if rest == "makes a charlie" {
+                    Event::Charlie { seat, hand }
+                } else if let Some(rest) = rest.strip_prefix("is paid ") {
+                    let (payout, result) = rest.split_once(" for ").ok_or_else(|| unknown(line))?;
+                    let result = [
+                        BetResult::Win,
+                        BetResult::Lose,
+                        BetResult::Surrender,
+                        BetResult::StandOff,
+                        BetResult::Blackjack(BlackjackPayout::ThreeToTwo),
+                        BetResult::Blackjack(BlackjackPayout::SixToFive),
+                        BetResult::Blackjack(BlackjackPayout::EvenMoney),
+                        BetResult::EvenMoney,
+                    ]
+                        .into_iter()
+                        .find(|r| result_name(r) == result)
+                        .ok_or_else(|| unknown(line))?;
+                    Event::HandSettled { seat, hand, result, payout: number(payout)? }
+                } else {
+                    return Err(unknown(line));
+                }
+            },
+        },
+    };
+
+    Ok(event)
+}
+
+fn chips_line(chips: &[u32]) -> String {
+    let chips: Vec<String> = chips.iter()
+        .enumerate()
+        .map(|(seat, chips)| format!("Player {} {}", seat + 1, chips))
+        .collect();
+
+    format!("Chips: {}", chips.join(", "))
+}
+
+/// One round read back from a hand history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoundRecord {
+    pub round: u32,
+    pub timestamp: u64,
+    /// Cards left in the shoe and in the discards when the deal began.
+    pub remaining: usize,
+    pub discarded: usize,
+    /// The round's events from [`Event::RoundStarted`] to [`Event::RoundEnded`].
+    pub events: Vec<Event>,
+    /// Every player's chips once the round was settled.
+    pub chips: Vec<u32>,
+}
+
+impl RoundRecord {
+    /// The cards the round took from the shoe, in the order they were dealt. The hole
+    /// card comes after the players' second cards, though it is only written once turned over.
+    pub fn cards(&self) -> Result<Vec<Card>> {
+        let mut cards: Vec<Option<Card>> = Vec::new();
+        let mut hole = None;
+        for event in self.events.iter() {
+            match event {
+                Event::PlayerCard { card, .. } | Event::DealerUpcard { card } | Event::DealerCard { card, .. } => {
+                    cards.push(Some(card.clone()));
+                },
+                Event::DealerHoleCard => {
+                    hole = Some(cards.len());
+                    cards.push(None);
+                },
+                Event::HoleCardRevealed { card, .. } => {
+                    if let Some(n) = hole {
+                        cards[n] = Some(card.clone());
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        cards.into_iter()
+            .collect::<Option<Vec<Card>>>()
+            .ok_or_else(|| Error::Parse(format!("round {} never shows the dealer's hole card", self.round)))
+    }
+}
+
+/// A seat that makes the decisions a round's history records for it.
+struct Recorded {
+    bet: Option<u32>,
+    side_bets: Vec<(SideBetKind, u32)>,
+    actions: VecDeque<Action>,
+    insurance: u32,
+    even_money: bool,
+    surrendered_early: bool,
+}
+
+impl Recorded {
+    fn new(events: &[Event], seat: usize) -> Recorded {
+        let mut recorded = Recorded {
+            bet: None,
+            side_bets: Vec::new(),
+            actions: VecDeque::new(),
+            insurance: 0,
+            even_money: false,
+            surrendered_early: false,
+        };
+        let mut previous: Option<&Event> = None;
+        for event in events.iter().filter(|e| e.seat() == Some(seat)) {
+            match event {
+                Event::BetPlaced { amount, .. } => recorded.bet = Some(*amount),
+                Event::SideBetPlaced { kind, amount, .. } => recorded.side_bets.push((*kind, *amount)),
+                Event::ActionTaken { action, .. } => recorded.actions.push_back(action.clone()),
+                Event::InsuranceTaken { amount, .. } => recorded.insurance = *amount,
+                Event::EvenMoneyPaid { .. } => recorded.even_money = true,
+                // A late surrender follows its action; an early one stands alone.
+                Event::Surrendered { .. } => {
+                    recorded.surrendered_early |= !matches!(previous, Some(Event::ActionTaken { .. }));
+                },
+                _ => (),
+            }
+            previous = Some(event);
+        }
+
+        recorded
+    }
+}
+
+impl PlayerInput for Recorded {
+    fn bet(&mut self, _table: &Table, _seat: usize) -> u32 {
+        self.bet.take().unwrap_or(0)
+    }
+
+    fn side_bets(&mut self, _table: &Table, _seat: usize) -> Vec<(SideBetKind, u32)> {
+        std::mem::take(&mut self.side_bets)
+    }
+
+    fn action(&mut self, _table: &Table, _seat: usize, _valid_moves: &[Action]) -> Action {
+        self.actions.pop_front().unwrap_or(Action::Stand)
+    }
+
+    fn insurance(&mut self, _table: &Table, _seat: usize) -> u32 {
+        self.insurance
+    }
+
+    fn even_money(&mut self, _table: &Table, _seat: usize) -> bool {
+        self.even_money
+    }
+
+    fn early_surrender(&mut self, _table: &Table, _seat: usize) -> bool {
+        self.surrendered_early
+    }
+}
+
+/// True for the events a round opens with, which a freshly seated table can't repeat.
+fn is_start(event: &Event) -> bool {
+    matches!(event, Event::RoundStarted { .. } | Event::Shuffled)
+}
+
+fn describe(event: Option<&Event>) -> String {
+    match event {
+        Some(Event::RoundEnded) => String::from("the end of the round"),
+        Some(event) => event_line(event).unwrap_or_default(),
+        None => String::from("nothing more"),
+    }
+}
+
+/// A whole hand history file, read back for replaying or checking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandHistory {
+    pub header: Header,
+    pub rounds: Vec<RoundRecord>,
+}
+
+impl HandHistory {
+    pub fn parse(source: &str) -> Result<HandHistory> {
+        let lines: Vec<&str> = source.lines().collect();
+        let header_end = lines.iter().position(|line| line.is_empty()).unwrap_or(lines.len());
+        let header = Header::parse(&lines[..header_end])?;
+        let mut rounds: Vec<RoundRecord> = Vec::new();
+        for &line in lines[header_end..].iter().filter(|line| !line.is_empty()) {
+            if let Some(rest) = line.strip_prefix("*** Round ") {
+                let (round, time) = rest.split_once(" *** ").ok_or_else(|| unknown(line))?;
+                let time = time.rsplit_once('(').and_then(|(_, t)| t.strip_suffix(')')).ok_or_else(|| unknown(line))?;
+                let round = number(round)?;
+                rounds.push(RoundRecord {
+                    round,
+                    timestamp: number(time)?,
+                    remaining: 0,
+                    discarded: 0,
+                    events: vec![Event::RoundStarted { round }],
+                    chips: Vec::new(),
+                });
+                continue;
+            }
+            let record = rounds.last_mut().ok_or_else(|| unknown(line))?;
+            if let Some(rest) = line.strip_prefix("Shoe: ") {
+                let (remaining, discarded) = rest.split_once(" cards left, ").ok_or_else(|| unknown(line))?;
+                record.remaining = number(remaining)?;
+                record.discarded = number(discarded.strip_suffix(" discarded").ok_or_else(|| unknown(line))?)?;
+            } else if let Some(rest) = line.strip_prefix("Chips: ") {
+                record.chips = rest.split(", ")
+                    .map(|player| number(player.rsplit(' ').next().unwrap_or(player)))
+                    .collect::<Result<Vec<u32>>>()?;
+                record.events.push(Event::RoundEnded);
+            } else {
+                record.events.push(parse_event(line)?);
+            }
+        }
+
+        Ok(HandHistory { header, rounds })
+    }
+
+    pub fn load(path: &str) -> Result<HandHistory> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| Error::Io(format!("Could not read {}: {}", path, e)))?;
+
+        HandHistory::parse(&source)
+    }
+
+    /// Each player's chips going into the round at `index`.
+    pub fn chips_before(&self, index: usize) -> Vec<u32> {
+        match index {
+            0 => self.header.starting_chips.clone(),
+            n => self.rounds[n - 1].chips.clone(),
+        }
+    }
+
+    /// Deals the round at `index` again from its recorded cards, with every seat making the
+    /// decisions the history records, and checks the table gives the same events and chips.
+    /// `observe` sees the table after each batch of events, the way a front-end would; the
+    /// first batch is the round's recorded start.
+    pub fn replay<F: FnMut(&Table, &[Event])>(&self, index: usize, mut observe: F) -> Result<()> {
+        let record = &self.rounds[index];
+        let mut deck = Deck::new();
+        deck.extend(record.cards()?.into_iter().rev().collect());
+        let players = PlayerList::from_chips(&self.chips_before(index));
+        let mut seats: Vec<Box<dyn PlayerInput>> = (0..self.header.players)
+            .map(|seat| Box::new(Recorded::new(&record.events, seat)) as Box<dyn PlayerInput>)
+            .collect();
+        let rules = self.header.rules.clone();
+        let mut table = Table::with_shoe(rules, players, self.header.min_bet, Shoe::stacked(deck));
+        // The replayed shoe holds every recorded card, so it never runs dry.
+        let recorded: Vec<&Event> = record.events.iter()
+            .filter(|e| !is_start(e) && **e != Event::ShoeRefilled)
+            .collect();
+        let mut replayed: Vec<Event> = Vec::new();
+        let mismatch = |index: usize, replayed: String| Error::HistoryMismatch {
+            round: record.round,
+            recorded: describe(recorded.get(index).copied()),
+            replayed,
+        };
+        table.start_round()?;
+        let start: Vec<Event> = record.events.iter().take_while(|e| is_start(e)).cloned().collect();
+        observe(&table, &start);
+        while table.phase() != Phase::RoundOver {
+            let events = input::step(&mut table, &mut seats).map_err(|e| mismatch(replayed.len(), e.to_string()))?;
+            observe(&table, &events);
+            replayed.extend(events);
+        }
+        for index in 0..recorded.len().max(replayed.len()) {
+            if recorded.get(index).copied() != replayed.get(index) {
+                return Err(mismatch(index, describe(replayed.get(index))));
+            }
+        }
+        let chips: Vec<u32> = table.players().iter().map(|p| p.chips.unwrap_or(0)).collect();
+        if chips != record.chips {
+            return Err(Error::HistoryMismatch {
+                round: record.round,
+                recorded: chips_line(&record.chips),
+                replayed: chips_line(&chips),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Replays every round, failing on the first that plays out differently.
+    pub fn verify(&self) -> Result<()> {
+        (0..self.rounds.len()).try_for_each(|index| self.replay(index, |_, _| ()))
+    }
+}
+
+/// Writes a session's hand history as the rounds are played.
+pub struct HistoryWriter<W: Write> {
+    out: W,
+}
+
+impl HistoryWriter<BufWriter<File>> {
+    /// The folder hand histories are saved to.
+    pub const DIR: &'static str = "hand_histories";
+
+    /// Starts a file named after the session in `dir`, returning the writer and the path.
+    pub fn create(dir: &str, header: &Header) -> Result<(HistoryWriter<BufWriter<File>>, String)> {
+        let path = Path::new(dir).join(format!("{}.txt", header.session));
+        let path = path.to_string_lossy().to_string();
+        fs::create_dir_all(dir)
+            .and_then(|_| File::create(&path))
+            .map_err(|e| Error::Io(format!("Could not write {}: {}", path, e)))
+            .and_then(|file| HistoryWriter::new(BufWriter::new(file), header))
+            .map(|writer| (writer, path))
+    }
+
+    /// Carries on the history at `path`, as when a saved game is resumed.
+    pub fn append(path: &str) -> Result<HistoryWriter<BufWriter<File>>> {
+        let file = fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| Error::Io(format!("Could not open {}: {}", path, e)))?;
+
+        Ok(HistoryWriter { out: BufWriter::new(file) })
+    }
+}
+
+impl<W: Write> HistoryWriter<W> {
+    /// Writes `header` to `out`, ready for the first round.
+    pub fn new(mut out: W, header: &Header) -> Result<HistoryWriter<W>> {
+        header.write(&mut out).map_err(write_error)?;
+
+        Ok(HistoryWriter { out })
+    }
+
+    /// Writes `events` as `table` left them. Call it with every batch of events the
+    /// table returns, in order; each finished round is flushed to the output.
+    pub fn record(&mut self, table: &Table, events: &[Event]) -> Result<()> {
+        self.write(table, events).map_err(write_error)
+    }
+
+    fn write(&mut self, table: &Table, events: &[Event]) -> std::io::Result<()> {
+        for event in events {
+            match event {
+                Event::RoundStarted { round } => {
+                    let now = timestamp();
+                    writeln!(self.out, "*** Round {} *** {} ({})", round, format_timestamp(now), now)?;
+                },
+                Event::RoundEnded => {
+                    let chips: Vec<u32> = table.players().iter().map(|p| p.chips.unwrap_or(0)).collect();
+                    writeln!(self.out, "{}\n", chips_line(&chips))?;
+                    self.out.flush()?;
+                },
+                event => writeln!(self.out, "{}", event_line(event).unwrap())?,
+            }
+        }
+        if events.iter().any(|e| matches!(e, Event::RoundStarted { .. })) {
+            let shoe = table.shoe();
+            writeln!(self.out, "Shoe: {} cards left, {} discarded", shoe.remaining(), shoe.discarded())?;
+        }
+
+        Ok(())
+    }
+
+    /// Hands back the output, flushed.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.out.flush().map_err(write_error)?;
+
+        Ok(self.out)
+    }
+}
+
+fn write_error(e: std::io::Error) -> Error {
+    Error::Io(format!("Could not write the hand history: {}", e))
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::input::{ self, BasicStrategy, Move, PlayerInput, Scripted };
+    use crate::player::PlayerList;
+    use crate::table::Phase;
+    use crate::table::tests::stacked_shoe;
+
+    pub fn event_lines() {
+        let card = Card::_test_card(11);
+        let events = vec![
+            Event::Shuffled,
+            Event::ShoeRefilled,
+            Event::BetPlaced { seat: 0, amount: 10 },
+            Event::SideBetPlaced { seat: 1, kind: SideBetKind::TwentyOnePlusThree, amount: 5 },
+            Event::PlayerCard { seat: 1, hand: 2, card: card.clone(), total: 21 },
+            Event::DealerUpcard { card: card.clone() },
+            Event::DealerHoleCard,
+            Event::SideBetSettled { seat: 1, kind: SideBetKind::TwentyOnePlusThree, outcome: Some(SideBetOutcome::StraightFlush), payout: 205 },
+            Event::SideBetSettled { seat: 0, kind: SideBetKind::PerfectPairs, outcome: None, payout: 0 },
+            Event::InsuranceOpen,
+            Event::InsuranceTaken { seat: 0, amount: 5 },
+            Event::EvenMoneyPaid { seat: 0, payout: 20 },
+            Event::Surrendered { seat: 0, hand: 0, refund: 5 },
+            Event::DealerPeeked { blackjack: true },
+            Event::DealerPeeked { blackjack: false },
+            Event::InsuranceSettled { seat: 0, payout: 15 },
+            Event::TurnStarted { seat: 0, hand: 1 },
+            Event::ActionTaken { seat: 0, hand: 1, action: Action::DoubleDown },
+            Event::HandBusted { seat: 0, hand: 1, total: 24 },
+            Event::Charlie { seat: 0, hand: 0 },
+            Event::HoleCardRevealed { card: card.clone(), total: 17 },
+            Event::DealerCard { card, total: 18 },
+            Event::DealerStands { total: 18 },
+            Event::DealerBusts { total: 22 },
+            Event::HandSettled { seat: 0, hand: 0, result: BetResult::Blackjack(BlackjackPayout::SixToFive), payout: 22 },
+            Event::HandSettled { seat: 2, hand: 3, result: BetResult::StandOff, payout: 10 },
+            Event::OriginalBetsOnly { seat: 0, refund: 10 },
+        ];
+        for event in events {
+            let line = event_line(&event).unwrap();
+            assert_eq!(parse_event(&line), Ok(event), "{}", line);
+        }
+        assert_eq!(
+            event_line(&Event::ActionTaken { seat: 0, hand: 0, action: Action::DoubleDown }).unwrap(),
+            "Player 1 hand 1 chooses to Double Down",
+        );
+        assert_eq!(event_line(&Event::RoundEnded), None);
+        assert_eq!(
+            event_line(&Event::SideBetSettled { seat: 0, kind: SideBetKind::PerfectPairs, outcome: Some(SideBetOutcome::MixedPair), payout: 35 }).unwrap(),
+            "Player 1 is paid 35 on Perfect Pairs (mixed pair)",
+        );
+        assert_eq!(
+            parse_event("Player 2 is paid 30 on Buster Blackjack (3 or 4 card bust)").unwrap(),
+            Event::SideBetSettled { seat: 1, kind: SideBetKind::BusterBlackjack, outcome: Some(SideBetOutcome::ThreeOrFourCardBust), payout: 30 },
+        );
+        assert!(parse_event("Player 0 bets 10").is_err());
+        assert!(parse_event("Player 1 bets 10 on Roulette").is_err());
+        assert!(parse_event("Dealer dances").is_err());
+    }
+
+    pub fn record_and_read_history() {
+        // Player 1 stands on 20 and player 2 hits 10, 6 to 21 with a 5; the dealer stands on 17.
+        let shoe = stacked_shoe(&[10, 10, 9, 10, 6, 8, 5]);
+        let rules = TableRules::vegas_strip();
+        let mut table = Table::with_shoe(rules.clone(), PlayerList::new(2, 100), 10, shoe);
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![
+            Box::new(BasicStrategy::default()),
+            Box::new(Scripted::new(vec![Move::Bet(20), Move::Action(Action::Hit)])),
+        ];
+        let header = Header::new(99, vec![100, 100], 10, rules);
+        let mut writer = HistoryWriter::new(Vec::new(), &header).unwrap();
+        let mut played = Vec::new();
+        let mut events = table.start_round().unwrap();
+        loop {
+            writer.record(&table, &events).unwrap();
+            played.extend(events);
+            if table.phase() == Phase::RoundOver {
+                break;
+            }
+            events = input::step(&mut table, &mut seats).unwrap();
+        }
+        let text = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert!(text.starts_with("Blackjack hand history, format 4\nSession: "));
+        assert!(text.contains("\nSeed: 99\nPlayers: 2\nStarting chips: 100, 100\n"));
+        assert!(text.contains("\n    decks = 6\n"));
+        assert!(text.contains("\nPlayer 2 bets 20\n"));
+        assert!(text.contains("\nPlayer 2 hand 1 gets 5 of Spades (total: 21)\n"));
+        assert!(text.contains("\nPlayer 2 hand 1 is paid 40 for a win\nChips: Player 1 110, Player 2 120\n"));
+
+        let history = HandHistory::parse(&text).unwrap();
+        assert_eq!(history.header, header);
+        // Format 1 histories give one starting count for every seat.
+        let format_1 = text.replace("format 4", "format 1").replace("Starting chips: 100, 100", "Starting chips: 100");
+        let old = HandHistory::parse(&format_1).unwrap();
+        assert_eq!(old.header.starting_chips, vec![100, 100]);
+        assert_eq!(old.rounds, history.rounds);
+        assert!(HandHistory::parse(&text.replace("format 4", "format 3")).is_ok());
+        assert_eq!(
+            HandHistory::parse(&text.replace("Starting chips: 100, 100", "Starting chips: 100")),
+            Err(Error::Parse(String::from("the header has starting chips for 1 of 2 players"))),
+        );
+        assert_eq!(history.rounds.len(), 1);
+        let round = &history.rounds[0];
+        assert_eq!(round.round, 1);
+        assert_eq!(round.events, played);
+        assert_eq!(round.chips, vec![110, 120]);
+        assert_eq!(round.remaining, 59);
+        assert!(HandHistory::parse(&text.replace("format 4", "format 5")).is_err());
+        assert!(HandHistory::parse("Some other file").is_err());
+    }
+
+    /// Plays up to `rounds` rounds, returning the hand history they leave.
+    fn record_session(mut table: Table, seats: &mut [Box<dyn PlayerInput>], rounds: u32) -> String {
+        let chips = table.players().iter().map(|p| p.chips.unwrap()).collect();
+        let header = Header::new(1, chips, table.min_bet(), table.rules().clone());
+        let mut writer = HistoryWriter::new(Vec::new(), &header).unwrap();
+        while table.players_left() && table.round() < rounds {
+            let mut events = table.start_round().unwrap();
+            loop {
+                writer.record(&table, &events).unwrap();
+                if table.phase() == Phase::RoundOver {
+                    break;
+                }
+                events = input::step(&mut table, seats).unwrap();
+            }
+        }
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    pub fn replay_rounds() {
+        // Player 1 stands on 19 against an Ace. Player 2 insures 8, 8, splits, doubles 8, 3
+        // into 21 and hits 8, 2 to 20; the dealer stands on a soft 18.
+        let shoe = stacked_shoe(&[10, 8, 11, 9, 8, 7, 3, 10, 2, 10]);
+        let table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(2, 100), 10, shoe);
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![
+            Box::new(BasicStrategy::default()),
+            Box::new(Scripted::new(vec![
+                Move::Bet(20),
+                Move::Insurance(true),
+                Move::Action(Action::Split),
+                Move::Action(Action::DoubleDown),
+                Move::Action(Action::Hit),
+                Move::Action(Action::Stand),
+            ])),
+        ];
+        let text = record_session(table, &mut seats, 1);
+        let history = HandHistory::parse(&text).unwrap();
+        assert_eq!(history.rounds[0].chips, vec![110, 150]);
+        assert_eq!(history.rounds[0].cards().unwrap().len(), 10);
+        let mut batches = 0;
+        assert_eq!(history.replay(0, |_, _| batches += 1), Ok(()));
+        assert!(batches > 5);
+
+        let tampered = HandHistory::parse(&text.replace("hand 2 is paid 40", "hand 2 is paid 60")).unwrap();
+        assert_eq!(tampered.verify(), Err(Error::HistoryMismatch {
+            round: 1,
+            recorded: String::from("Player 2 hand 2 is paid 60 for a win"),
+            replayed: String::from("Player 2 hand 2 is paid 40 for a win"),
+        }));
+        let tampered = HandHistory::parse(&text.replace("Chips: Player 1 110", "Chips: Player 1 120")).unwrap();
+        assert!(matches!(tampered.verify(), Err(Error::HistoryMismatch { round: 1, .. })));
+
+        // Whole sessions of bots, shuffles included, replay exactly under both hole card rules.
+        for rules in [TableRules::vegas_strip(), TableRules::european()] {
+            let table = Table::with_shoe(rules, PlayerList::new(3, 100), 10, Shoe::seeded(1, 0.75, 5));
+            let mut seats: Vec<Box<dyn PlayerInput>> = (1..=3)
+                .map(|units| Box::new(BasicStrategy::new(units)) as Box<dyn PlayerInput>)
+                .collect();
+            let history = HandHistory::parse(&record_session(table, &mut seats, 40)).unwrap();
+            assert!(history.rounds.len() > 10);
+            assert!(history.rounds.iter().any(|r| r.events.contains(&Event::Shuffled)));
+            assert_eq!(history.verify(), Ok(()));
+        }
+
+        // A shoe that runs dry on round 2's hole card replays from the discards it took back.
+        let mut deck = Deck::new();
+        deck.deal(52);
+        deck.extend([10, 9, 8, 8, 10, 9, 5].iter().rev().map(|&v| Card::_test_card(v)).collect());
+        let table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(1, 100), 10, Shoe::stacked(deck));
+        let stand = || vec![Move::Bet(10), Move::Action(Action::Stand)];
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![Box::new(Scripted::new([stand(), stand()].concat()))];
+        let text = record_session(table, &mut seats, 2);
+        assert!(text.contains("\nPlayer 1 hand 1 gets 5 of Spades (total: 15)\nShoe refilled from the discards\nDealer takes a hole card\n"));
+        assert_eq!(HandHistory::parse(&text).unwrap().verify(), Ok(()));
+    }
+}
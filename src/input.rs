@@ -0,0 +1,320 @@
+use crate::error::Result;
+use crate::player::Action;
+use crate::side_bet::SideBetKind;
+use crate::strategy;
+use crate::table::{ Decision, Event, Table };
+use std::collections::VecDeque;
+
+/// Whoever makes the decisions for a seat: a person at the keyboard, a bot or a script.
+/// Every method is handed the table as it stands and the seat being asked.
+pub trait PlayerInput {
+    /// How much to bet this round, at least the table minimum unless going all-in.
+    fn bet(&mut self, table: &Table, seat: usize) -> u32;
+
+    /// Which of the table's side bets to stake alongside the bet, each up to the bet.
+    /// Defaults to none.
+    fn side_bets(&mut self, _table: &Table, _seat: usize) -> Vec<(SideBetKind, u32)> {
+        Vec::new()
+    }
+
+    /// Which of `valid_moves` to play on the seat's active hand.
+    fn action(&mut self, table: &Table, seat: usize, valid_moves: &[Action]) -> Action;
+
+    /// How much insurance to stake, up to half the bet; 0 declines.
+    fn insurance(&mut self, table: &Table, seat: usize) -> u32;
+
+    /// Whether to take even money on a blackjack. Even money is insurance on a natural,
+    /// so this defaults to taking it whenever the seat would insure.
+    fn even_money(&mut self, table: &Table, seat: usize) -> bool {
+        self.insurance(table, seat) > 0
+    }
+
+    /// Whether to surrender before the dealer checks for blackjack.
+    fn early_surrender(&mut self, table: &Table, seat: usize) -> bool;
+}
+
+/// Answers the table's next decision with the input sitting in that seat, or advances
+/// the table when nothing is pending.
+pub fn step(table: &mut Table, seats: &mut [Box<dyn PlayerInput>]) -> Result<Vec<Event>> {
+    match table.next_decision() {
+        Some(Decision::Bet { seat }) => {
+            let amount = seats[seat].bet(table, seat);
+            table.place_bet(seat, amount)
+        },
+        Some(Decision::SideBets { seat }) => {
+            let bets = seats[seat].side_bets(table, seat);
+            table.place_side_bets(seat, &bets)
+        },
+        Some(Decision::Insurance { seat }) => {
+            let amount = seats[seat].insurance(table, seat);
+            table.insure(seat, amount)
+        },
+        Some(Decision::EvenMoney { seat }) => {
+            let accept = seats[seat].even_money(table, seat);
+            table.even_money(seat, accept)
+        },
+        Some(Decision::EarlySurrender { seat }) => {
+            let accept = seats[seat].early_surrender(table, seat);
+            table.early_surrender(seat, accept)
+        },
+        Some(Decision::Action { seat, .. }) => {
+            let action = seats[seat].action(table, seat, &table.valid_moves());
+            table.act(action)
+        },
+        None => table.advance(),
+    }
+}
+
+/// The table minimum, or everything the seat has left if that is less.
+fn flat_bet(table: &Table, seat: usize, units: u32) -> u32 {
+    let chips = table.players()[seat].chips.unwrap();
+    chips.min(table.min_bet() * units)
+}
+
+/// A bot that flat bets, never insures and plays basic strategy.
+pub struct BasicStrategy {
+    /// The bet, in multiples of the table minimum.
+    pub units: u32,
+}
+
+impl BasicStrategy {
+    pub fn new(units: u32) -> BasicStrategy {
+        BasicStrategy { units }
+    }
+}
+
+impl Default for BasicStrategy {
+    fn default() -> Self {
+        BasicStrategy::new(1)
+    }
+}
+
+impl PlayerInput for BasicStrategy {
+    fn bet(&mut self, table: &Table, seat: usize) -> u32 {
+        flat_bet(table, seat, self.units)
+    }
+
+    fn action(&mut self, table: &Table, seat: usize, valid_moves: &[Action]) -> Action {
+        let hand = table.players()[seat].hand();
+        strategy::best_action(hand, table.upcard().unwrap(), table.rules(), valid_moves)
+    }
+
+    fn insurance(&mut self, _table: &Table, _seat: usize) -> u32 {
+        0
+    }
+
+    fn early_surrender(&mut self, table: &Table, seat: usize) -> bool {
+        strategy::early_surrender(table.players()[seat].hand(), table.upcard().unwrap())
+    }
+}
+
+/// One answer in a [`Scripted`] seat's list.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Move {
+    Bet(u32),
+    SideBets(Vec<(SideBetKind, u32)>),
+    Action(Action),
+    /// Insurance for as much as the seat can stake, or none; on a blackjack, whether
+    /// to take even money.
+    Insurance(bool),
+    Surrender(bool),
+}
+
+/// A seat that plays a fixed list of moves in order, for tests and reproducible games.
+///
+/// # Panics
+///
+/// Panics when asked for a decision the next move does not answer, or when the script
+/// runs out.
+pub struct Scripted {
+    moves: VecDeque<Move>,
+}
+
+impl Scripted {
+    pub fn new(moves: Vec<Move>) -> Scripted {
+        Scripted { moves: moves.into() }
+    }
+
+    /// The moves not played yet.
+    pub fn remaining(&self) -> usize {
+        self.moves.len()
+    }
+
+    fn next(&mut self, expected: &str) -> Move {
+        match self.moves.pop_front() {
+            Some(next) => next,
+            None => panic!("script ran out of moves, expected {}", expected),
+        }
+    }
+}
+
+impl PlayerInput for Scripted {
+    fn bet(&mut self, _table: &Table, _seat: usize) -> u32 {
+        match self.next("a bet") {
+            Move::Bet(amount) => amount,
+            other => panic!("expected a bet, the script has {:?}", other),
+        }
+    }
+
+    fn side_bets(&mut self, _table: &Table, _seat: usize) -> Vec<(SideBetKind, u32)> {
+        match self.next("side bets") {
+            Move::SideBets(bets) => bets,
+            other => panic!("expected side bets, the script has {:?}", other),
+        }
+    }
+
+    fn action(&mut self, _table: &Table, _seat: usize, _valid_moves: &[Action]) -> Action {
+        match self.next("an action") {
+            Move::Action(action) => action,
+            other => panic!("expected an action, the script has {:?}", other),
+        }
+    }
+
+    fn insurance(&mut self, table: &Table, seat: usize) -> u32 {
+        match self.next("an insurance answer") {
+            Move::Insurance(true) => table.players()[seat].max_insurance(),
+            Move::Insurance(false) => 0,
+            other => panic!("expected an insurance answer, the script has {:?}", other),
+        }
+    }
+
+    fn early_surrender(&mut self, _table: &Table, _seat: usize) -> bool {
+        match self.next("a surrender answer") {
+            Move::Surrender(accept) => accept,
+            other => panic!("expected a surrender answer, the script has {:?}", other),
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::player::{ BetResult, PlayerList };
+    use crate::error::Error;
+    use crate::rules::TableRules;
+    use crate::side_bet::{ SideBet, SideBetOutcome };
+    use crate::table::Phase;
+    use crate::table::tests::stacked_shoe;
+
+    fn play_round(table: &mut Table, seats: &mut [Box<dyn PlayerInput>]) -> Vec<Event> {
+        let mut events = table.start_round().unwrap();
+        while table.phase() != Phase::RoundOver {
+            events.extend(step(table, seats).unwrap());
+        }
+
+        events
+    }
+
+    pub fn scripted_and_bot_seats() {
+        // Player 1 (scripted): 10, 6 hits a 3; player 2 (bot): 10, 7 stands; dealer 10, 8.
+        let shoe = stacked_shoe(&[10, 10, 10, 6, 7, 8, 3]);
+        let mut table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(2, 100), 10, shoe);
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![
+            Box::new(Scripted::new(vec![Move::Bet(20), Move::Action(Action::Hit), Move::Action(Action::Stand)])),
+            Box::new(BasicStrategy::default()),
+        ];
+        let events = play_round(&mut table, &mut seats);
+        assert!(events.contains(&Event::BetPlaced { seat: 0, amount: 20 }));
+        assert!(events.contains(&Event::BetPlaced { seat: 1, amount: 10 }));
+        assert!(events.contains(&Event::ActionTaken { seat: 1, hand: 0, action: Action::Stand }));
+        assert!(events.contains(&Event::HandSettled { seat: 0, hand: 0, result: BetResult::Win, payout: 40 }));
+        assert!(events.contains(&Event::HandSettled { seat: 1, hand: 0, result: BetResult::Lose, payout: 0 }));
+        assert_eq!(table.players()[0].chips, Some(120));
+        assert_eq!(table.players()[1].chips, Some(90));
+    }
+
+    /// Bets 10, stands and stakes a fixed amount of insurance.
+    struct Insures(u32);
+
+    impl PlayerInput for Insures {
+        fn bet(&mut self, _table: &Table, _seat: usize) -> u32 {
+            10
+        }
+
+        fn action(&mut self, _table: &Table, _seat: usize, _valid_moves: &[Action]) -> Action {
+            Action::Stand
+        }
+
+        fn insurance(&mut self, _table: &Table, _seat: usize) -> u32 {
+            self.0
+        }
+
+        fn early_surrender(&mut self, _table: &Table, _seat: usize) -> bool {
+            false
+        }
+    }
+
+    pub fn partial_insurance() {
+        // Player: 10, 9 insures for 3 of the 5 allowed; dealer Ace up, 5 in the hole.
+        let shoe = stacked_shoe(&[10, 11, 9, 5]);
+        let mut table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(1, 100), 10, shoe);
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![Box::new(Insures(3))];
+        let events = play_round(&mut table, &mut seats);
+        assert!(events.contains(&Event::InsuranceTaken { seat: 0, amount: 3 }));
+        assert!(events.contains(&Event::InsuranceSettled { seat: 0, payout: 0 }));
+    }
+
+    pub fn bot_bets_what_is_left() {
+        let mut table = Table::new(TableRules::vegas_strip(), PlayerList::new(1, 100), 10);
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![Box::new(BasicStrategy::new(20))];
+        table.start_round().unwrap();
+        let events = step(&mut table, &mut seats).unwrap();
+        assert_eq!(events, vec![Event::BetPlaced { seat: 0, amount: 100 }]);
+    }
+
+    pub fn side_bets_at_table() {
+        // Player: a pair of 8s of Spades stands; dealer King of Spades up, 7 in the hole.
+        let rules = TableRules {
+            side_bets: SideBetKind::ALL.into_iter().map(SideBet::standard).collect(),
+            ..TableRules::vegas_strip()
+        };
+        let shoe = stacked_shoe(&[8, 10, 8, 7]);
+        let mut table = Table::with_shoe(rules, PlayerList::new(1, 100), 10, shoe);
+        table.start_round().unwrap();
+        table.place_bet(0, 10).unwrap();
+        assert_eq!(table.next_decision(), Some(Decision::SideBets { seat: 0 }));
+        let too_large = [(SideBetKind::TwentyOnePlusThree, 5), (SideBetKind::PerfectPairs, 11)];
+        assert_eq!(table.place_side_bets(0, &too_large), Err(Error::SideBetTooLarge));
+        // Nothing was staked by the failed attempt.
+        assert_eq!(table.players()[0].chips, Some(90));
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![Box::new(Scripted::new(vec![
+            Move::SideBets(vec![(SideBetKind::TwentyOnePlusThree, 5), (SideBetKind::PerfectPairs, 10)]),
+            Move::Action(Action::Stand),
+        ]))];
+        let mut events = Vec::new();
+        while table.phase() != Phase::RoundOver {
+            events.extend(step(&mut table, &mut seats).unwrap());
+        }
+        assert!(events.contains(&Event::SideBetPlaced { seat: 0, kind: SideBetKind::PerfectPairs, amount: 10 }));
+        assert!(events.contains(&Event::SideBetSettled {
+            seat: 0,
+            kind: SideBetKind::TwentyOnePlusThree,
+            outcome: Some(SideBetOutcome::Flush),
+            payout: 30,
+        }));
+        assert!(events.contains(&Event::SideBetSettled {
+            seat: 0,
+            kind: SideBetKind::PerfectPairs,
+            outcome: Some(SideBetOutcome::PerfectPair),
+            payout: 260,
+        }));
+        assert!(events.contains(&Event::HandSettled { seat: 0, hand: 0, result: BetResult::Lose, payout: 0 }));
+        assert_eq!(table.players()[0].chips, Some(365));
+
+        // Side bets the table does not offer are refused, and bots decline them.
+        let mut table = Table::new(TableRules::vegas_strip(), PlayerList::new(1, 100), 10);
+        table.start_round().unwrap();
+        table.place_bet(0, 10).unwrap();
+        assert_eq!(table.next_decision(), None);
+        let rules = TableRules { side_bets: vec![SideBet::standard(SideBetKind::PerfectPairs)], ..TableRules::vegas_strip() };
+        let mut table = Table::new(rules, PlayerList::new(1, 100), 10);
+        table.start_round().unwrap();
+        table.place_bet(0, 10).unwrap();
+        assert_eq!(table.place_side_bets(0, &[(SideBetKind::TwentyOnePlusThree, 5)]), Err(Error::SideBetNotOffered));
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![Box::new(BasicStrategy::default())];
+        assert_eq!(step(&mut table, &mut seats).unwrap(), vec![]);
+        assert_eq!(table.next_decision(), None);
+    }
+}
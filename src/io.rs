@@ -1,11 +1,10 @@
-use crate::player::{ Player, Action };
+use blackjack::{ strategy, Action, PlayerInput, SideBetKind, Table };
 use std::{ io, str, thread, time };
 
 #[allow(dead_code)]
-fn get_user_str(prompt: Option<&str>) -> String {
-    match prompt {
-        Some(s) => println!("{}", s),
-        None => (),
+pub fn get_user_str(prompt: Option<&str>) -> String {
+    if let Some(s) = prompt {
+        println!("{}", s);
     }
 
     let mut input = String::new();
@@ -40,27 +39,27 @@ pub fn get_clamped_user_int<T>(prompt: Option<&str>, min: T, max: T) -> T
     }
 }
 
+/// Lists `valid_moves` plus a last "Hint" option that prints `hint` and asks again.
 #[allow(dead_code)]
-pub fn get_user_action(player: &mut Player) -> Action {
+pub fn get_user_action(valid_moves: &[Action], hint: &Action) -> Action {
     let mut prompt = String::from("Type the number of your desired action:");
-    let valid_moves = player.valid_moves();
     for (i, valid_move) in valid_moves.iter().enumerate() {
-        prompt.push_str(
-            &format!(
-                "\n{}: {}", 
-                i,
-                match valid_move {
-                    Action::Hit => "Hit",
-                    Action::Stand => "Stand",
-                    Action::DoubleDown => "Double Down",
-                    Action::Surrender => "Surrender",
-                },
-            )
-        );
+        prompt.push_str(&format!("\n{}: {}", i, valid_move));
     }
-    let option = get_clamped_user_int(Some(&prompt), 0, valid_moves.len());
+    prompt.push_str(&format!("\n{}: Hint", valid_moves.len()));
+    loop {
+        match get_clamped_user_int(Some(&prompt), 0, valid_moves.len()) {
+            n if n == valid_moves.len() => println!("Basic strategy says: {}", hint),
+            n => return valid_moves[n].clone(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn get_user_confirmation(prompt: &str) -> bool {
+    let prompt = format!("{}\n0: No\n1: Yes", prompt);
 
-    valid_moves[option].clone()
+    get_clamped_user_int(Some(&prompt), 0, 1) == 1
 }
 
 #[allow(dead_code)]
@@ -68,3 +67,76 @@ pub fn sleep(duration: u64) {
     let time = time::Duration::from_secs(duration);
     thread::sleep(time);
 }
+
+/// A seat played by a person at the keyboard. With `coach` set, every move that
+/// differs from basic strategy is flagged once it is made.
+pub struct Stdin {
+    pub coach: bool,
+}
+
+impl PlayerInput for Stdin {
+    fn bet(&mut self, table: &Table, seat: usize) -> u32 {
+        let player = &table.players()[seat];
+        let chips = player.chips.unwrap();
+        println!("\n{} to bet, chips: {}", player, chips);
+        match chips {
+            n if n <= table.min_bet() => n,
+            _ => get_clamped_user_int(
+                Some(&format!("How much would you like to bet? (minimum bet {})", table.min_bet())), 
+                table.min_bet(), 
+                chips,
+            ),
+        }
+    }
+
+    fn side_bets(&mut self, table: &Table, seat: usize) -> Vec<(SideBetKind, u32)> {
+        let player = &table.players()[seat];
+        let mut chips = player.chips.unwrap();
+        let mut bets = Vec::new();
+        for side_bet in table.rules().side_bets.iter() {
+            let max = chips.min(player.hands[0].pot());
+            if max == 0 {
+                break;
+            }
+            println!("{}", side_bet);
+            let amount = get_clamped_user_int(
+                Some(&format!("How much on {}? (0 to skip, up to {})", side_bet.kind, max)),
+                0,
+                max,
+            );
+            chips -= amount;
+            bets.push((side_bet.kind, amount));
+        }
+
+        bets
+    }
+
+    fn action(&mut self, table: &Table, seat: usize, valid_moves: &[Action]) -> Action {
+        let hand = table.players()[seat].hand();
+        let best = strategy::best_action(hand, table.upcard().unwrap(), table.rules(), valid_moves);
+        let action = get_user_action(valid_moves, &best);
+        if self.coach && action != best {
+            println!("Basic strategy would {} here, not {}", best, action);
+        }
+
+        action
+    }
+
+    fn insurance(&mut self, table: &Table, seat: usize) -> u32 {
+        let player = &table.players()[seat];
+        let max_insurance = player.max_insurance();
+        get_clamped_user_int(
+            Some(&format!("\n{}, how much insurance would you like? (max: {}, 0 to decline)", player, max_insurance)),
+            0,
+            max_insurance,
+        )
+    }
+
+    fn even_money(&mut self, table: &Table, seat: usize) -> bool {
+        get_user_confirmation(&format!("\n{}, you have blackjack. Take even money?", table.players()[seat]))
+    }
+
+    fn early_surrender(&mut self, table: &Table, seat: usize) -> bool {
+        get_user_confirmation(&format!("\n{}, surrender before the dealer checks for blackjack?", table.players()[seat]))
+    }
+}
@@ -0,0 +1,198 @@
+use crate::profile::{ Profiles, Session };
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Which sessions count towards a leaderboard or session list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionFilter {
+    /// Only sessions started at or after this time, in seconds since the Unix epoch.
+    pub from: Option<u64>,
+    /// Only sessions started before this time.
+    pub before: Option<u64>,
+    /// Only sessions played under the rules of this name, ignoring case.
+    pub rules: Option<String>,
+}
+
+impl SessionFilter {
+    pub fn matches(&self, session: &Session) -> bool {
+        self.from.iter().all(|&from| session.started >= from)
+            && self.before.iter().all(|&before| session.started < before)
+            && self.rules.iter().all(|rules| session.rules.eq_ignore_ascii_case(rules))
+    }
+}
+
+/// What a leaderboard ranks profiles by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ranking {
+    Net,
+    Roi,
+    PeakBankroll,
+    WinStreak,
+}
+
+impl Ranking {
+    pub const ALL: [Ranking; 4] = [Ranking::Net, Ranking::Roi, Ranking::PeakBankroll, Ranking::WinStreak];
+
+    /// The name used on the command line.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Ranking::Net => "net",
+            Ranking::Roi => "roi",
+            Ranking::PeakBankroll => "peak",
+            Ranking::WinStreak => "streak",
+        }
+    }
+
+    fn compare(&self, a: &Standing, b: &Standing) -> Ordering {
+        match self {
+            Ranking::Net => a.net.cmp(&b.net),
+            Ranking::Roi => a.roi().total_cmp(&b.roi()),
+            Ranking::PeakBankroll => a.peak_bankroll.cmp(&b.peak_bankroll),
+            Ranking::WinStreak => a.longest_streak.cmp(&b.longest_streak),
+        }
+    }
+}
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Ranking::Net => "net winnings",
+            Ranking::Roi => "ROI",
+            Ranking::PeakBankroll => "peak bankroll",
+            Ranking::WinStreak => "longest win streak",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A profile's results over the sessions a leaderboard counts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub sessions: u32,
+    pub rounds: u32,
+    pub wagered: u64,
+    pub net: i64,
+    pub peak_bankroll: u32,
+    /// The longest run of won rounds in any one session.
+    pub longest_streak: u32,
+}
+
+impl Standing {
+    /// Net winnings as a share of the chips wagered.
+    pub fn roi(&self) -> f64 {
+        match self.wagered {
+            0 => 0.0,
+            wagered => self.net as f64 / wagered as f64,
+        }
+    }
+}
+
+impl fmt::Display for Standing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: net {:+}, ROI {:+.1}%, peak bankroll {}, longest win streak {} ({} sessions, {} rounds)",
+            self.name,
+            self.net,
+            100.0 * self.roi(),
+            self.peak_bankroll,
+            self.longest_streak,
+            self.sessions,
+            self.rounds,
+        )
+    }
+}
+
+/// Ranks every profile with a session matching `filter` by `ranking`, best first. Ties
+/// go in name order.
+pub fn leaderboard(profiles: &Profiles, filter: &SessionFilter, ranking: Ranking) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = profiles.profiles.iter()
+        .filter_map(|profile| {
+            let mut standing = Standing { name: profile.name.clone(), ..Standing::default() };
+            for session in profile.sessions.iter().filter(|s| filter.matches(s)) {
+                standing.sessions += 1;
+                standing.rounds += session.rounds;
+                standing.wagered += session.wagered;
+                standing.net += session.net;
+                standing.peak_bankroll = standing.peak_bankroll.max(session.peak_bankroll);
+                standing.longest_streak = standing.longest_streak.max(session.longest_streak);
+            }
+            Some(standing).filter(|s| s.sessions > 0)
+        })
+        .collect();
+    standings.sort_by(|a, b| ranking.compare(b, a).then_with(|| a.name.cmp(&b.name)));
+
+    standings
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::progress::parse_date;
+
+    fn session(date: &str, rules: &str, wagered: u64, net: i64, peak_bankroll: u32, longest_streak: u32) -> Session {
+        let started = parse_date(date).unwrap();
+        Session {
+            started,
+            ended: started + 3_600,
+            rules: String::from(rules),
+            rounds: 10,
+            wagered,
+            net,
+            peak_bankroll,
+            longest_streak,
+            streak: 0,
+        }
+    }
+
+    pub fn rank_profiles() {
+        let mut profiles = Profiles::default();
+        for name in ["Ada", "Grace", "Alan"] {
+            profiles.create(name, 500).unwrap();
+        }
+        profiles.get_mut("Ada").unwrap().sessions = vec![
+            session("2024-01-05", "Vegas Strip", 400, 60, 560, 3),
+            session("2024-02-10", "European", 200, -40, 520, 5),
+        ];
+        profiles.get_mut("Grace").unwrap().sessions = vec![session("2024-02-01", "Vegas Strip", 100, 30, 700, 2)];
+
+        let everything = SessionFilter::default();
+        let names = |ranking| -> Vec<String> {
+            leaderboard(&profiles, &everything, ranking).into_iter().map(|s| s.name).collect()
+        };
+        assert_eq!(names(Ranking::Net), vec!["Grace", "Ada"]);
+        assert_eq!(names(Ranking::Roi), vec!["Grace", "Ada"]);
+        assert_eq!(names(Ranking::PeakBankroll), vec!["Grace", "Ada"]);
+        assert_eq!(names(Ranking::WinStreak), vec!["Ada", "Grace"]);
+        let ada = &leaderboard(&profiles, &everything, Ranking::Net)[1];
+        assert_eq!(ada, &Standing {
+            name: String::from("Ada"),
+            sessions: 2,
+            rounds: 20,
+            wagered: 600,
+            net: 20,
+            peak_bankroll: 560,
+            longest_streak: 5,
+        });
+        assert_eq!(
+            format!("{}", ada),
+            "Ada: net +20, ROI +3.3%, peak bankroll 560, longest win streak 5 (2 sessions, 20 rounds)",
+        );
+
+        // Filters pick sessions by start date and rules, leaving out profiles with none.
+        let vegas = SessionFilter { rules: Some(String::from("vegas strip")), ..SessionFilter::default() };
+        let standings = leaderboard(&profiles, &vegas, Ranking::Net);
+        assert_eq!(standings.iter().map(|s| (s.name.as_str(), s.net)).collect::<Vec<_>>(), vec![("Ada", 60), ("Grace", 30)]);
+        let february = SessionFilter {
+            from: parse_date("2024-02-01"),
+            before: parse_date("2024-02-10"),
+            rules: None,
+        };
+        let standings = leaderboard(&profiles, &february, Ranking::Net);
+        assert_eq!(standings.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Grace"]);
+        assert!(leaderboard(&profiles, &SessionFilter { rules: Some(String::from("Atlantic City")), ..vegas }, Ranking::Net).is_empty());
+    }
+}
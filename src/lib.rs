@@ -1,17 +1,103 @@
-mod deck;
-mod player;
-mod io;
+//! A blackjack engine: cards, shoes, hands, players, table rules and the round logic
+//! that ties them together. Nothing in the library reads from stdin or sleeps, so it
+//! can be driven by the bundled CLI, by tests or by your own front-end.
+//!
+//! ```
+//! use blackjack::{ Action, Decision, Phase, PlayerList, Table, TableRules };
+//!
+//! let mut table = Table::new(TableRules::vegas_strip(), PlayerList::new(1, 100), 10);
+//! table.start_round().unwrap();
+//! while table.phase() != Phase::RoundOver {
+//!     let events = match table.next_decision() {
+//!         Some(Decision::Bet { seat }) => table.place_bet(seat, 10),
+//!         Some(Decision::SideBets { seat }) => table.place_side_bets(seat, &[]),
+//!         Some(Decision::Insurance { seat }) => table.insure(seat, 0),
+//!         Some(Decision::EvenMoney { seat }) => table.even_money(seat, false),
+//!         Some(Decision::EarlySurrender { seat }) => table.early_surrender(seat, false),
+//!         Some(Decision::Action { .. }) => table.act(Action::Stand),
+//!         None => table.advance(),
+//!     };
+//!     for event in events.unwrap() {
+//!         println!("{:?}", event);
+//!     }
+//! }
+//! assert_eq!(table.round(), 1);
+//! ```
+
+pub mod count;
+pub mod deck;
+pub mod error;
+pub mod ev;
+pub mod hand;
+pub mod history;
+pub mod input;
+pub mod leaderboard;
+pub mod player;
+pub mod profile;
+pub mod progress;
+pub mod round;
+pub mod rules;
+pub mod save;
+pub mod shoe;
+pub mod side_bet;
+pub mod sim;
+pub mod strategy;
+pub mod table;
+pub mod tracker;
+pub mod training;
+
+pub use count::{ CountSystem, Counter };
+pub use deck::{ Card, Deck, Value };
+pub use error::{ Error, Result };
+pub use input::{ BasicStrategy, Move, PlayerInput, Scripted };
+pub use hand::Hand;
+pub use leaderboard::{ Ranking, SessionFilter, Standing };
+pub use history::{ HandHistory, Header, HistoryWriter, RoundRecord };
+pub use player::{ Action, BetResult, Player, PlayerList };
+pub use profile::{ LifetimeStats, Profile, Profiles, Session };
+pub use progress::{ DrillHistory, DrillRecord };
+pub use rules::{ BlackjackPayout, DoubleRule, HoleCardRule, SurrenderRule, TableRules };
+pub use save::SavedGame;
+pub use shoe::Shoe;
+pub use side_bet::{ Odds, SideBet, SideBetKind, SideBetOutcome };
+pub use sim::{ BetSpread, SimOptions, SimResult, SimStrategy };
+pub use table::{ Decision, Event, Phase, Table };
+pub use tracker::{ HandCategory, MistakeTracker, SessionReport, Spot };
+pub use training::{ Situation, TrainingProgress };
 
 #[cfg(test)]
 mod tests {
+    use super::count::tests as count_tests;
     use super::deck::tests as deck_tests;
+    use super::ev::tests as ev_tests;
+    use super::hand::tests as hand_tests;
+    use super::history::tests as history_tests;
+    use super::input::tests as input_tests;
+    use super::leaderboard::tests as leaderboard_tests;
     use super::player::tests as player_tests;
+    use super::profile::tests as profile_tests;
+    use super::progress::tests as progress_tests;
+    use super::round::tests as round_tests;
+    use super::rules::tests as rules_tests;
+    use super::save::tests as save_tests;
+    use super::shoe::tests as shoe_tests;
+    use super::side_bet::tests as side_bet_tests;
+    use super::sim::tests as sim_tests;
+    use super::strategy::tests as strategy_tests;
+    use super::table::tests as table_tests;
+    use super::tracker::tests as tracker_tests;
+    use super::training::tests as training_tests;
 
     #[test]
     fn create_card() {
         deck_tests::create_card();
     }
 
+    #[test]
+    fn parse_cards() {
+        deck_tests::parse_cards();
+    }
+
     #[test]
     fn create_deck() {
         deck_tests::create_deck();
@@ -22,16 +108,46 @@ mod tests {
         deck_tests::shuffle_deck();
     }
 
+    #[test]
+    fn seeded_shuffle() {
+        deck_tests::seeded_shuffle();
+    }
+
     #[test]
     fn deal_from_deck() {
         deck_tests::deal_from_deck();
     }
 
+    #[test]
+    fn combine_decks() {
+        deck_tests::combine_decks();
+    }
+
+    #[test]
+    fn create_shoe() {
+        shoe_tests::create_shoe();
+    }
+
+    #[test]
+    fn reach_cut_card() {
+        shoe_tests::reach_cut_card();
+    }
+
+    #[test]
+    fn deal_from_dry_shoe() {
+        shoe_tests::deal_from_dry_shoe();
+    }
+
     #[test]
     fn create_player() {
         player_tests::create_player();
     }
 
+    #[test]
+    fn name_player() {
+        player_tests::name_player();
+    }
+
     #[test]
     fn deal_player_cards() {
         player_tests::deal_player_cards();
@@ -42,6 +158,11 @@ mod tests {
         player_tests::check_valid_moves();
     }
 
+    #[test]
+    fn check_rule_moves() {
+        player_tests::check_rule_moves();
+    }
+
     #[test]
     fn make_bet() {
         player_tests::make_bet();
@@ -57,6 +178,41 @@ mod tests {
         player_tests::resolve_bet();
     }
 
+    #[test]
+    fn split_pair() {
+        player_tests::split_pair();
+    }
+
+    #[test]
+    fn resplit_hands() {
+        player_tests::resplit_hands();
+    }
+
+    #[test]
+    fn split_aces() {
+        player_tests::split_aces();
+    }
+
+    #[test]
+    fn resolve_split_hands() {
+        player_tests::resolve_split_hands();
+    }
+
+    #[test]
+    fn lose_original_bet() {
+        player_tests::lose_original_bet();
+    }
+
+    #[test]
+    fn insure_bet() {
+        player_tests::insure_bet();
+    }
+
+    #[test]
+    fn resolve_insurance() {
+        player_tests::resolve_insurance();
+    }
+
     #[test]
     fn create_player_list() {
         player_tests::create_player_list();
@@ -66,4 +222,269 @@ mod tests {
     fn clear_player_cards() {
         player_tests::clear_player_cards();
     }
+
+    #[test]
+    fn dealer_peeks() {
+        rules_tests::dealer_peeks();
+    }
+
+    #[test]
+    fn check_presets() {
+        rules_tests::check_presets();
+    }
+
+    #[test]
+    fn pay_blackjack() {
+        rules_tests::pay_blackjack();
+    }
+
+    #[test]
+    fn load_rules_from_toml() {
+        rules_tests::load_rules_from_toml();
+    }
+
+    #[test]
+    fn dealer_hits_soft_17() {
+        rules_tests::dealer_hits_soft_17();
+    }
+
+    #[test]
+    fn settle_hand_results() {
+        round_tests::settle_hand_results();
+    }
+
+    #[test]
+    fn play_round_phases() {
+        table_tests::play_round_phases();
+    }
+
+    #[test]
+    fn bust_and_dealer_draws() {
+        table_tests::bust_and_dealer_draws();
+    }
+
+    #[test]
+    fn split_hands_in_turn() {
+        table_tests::split_hands_in_turn();
+    }
+
+    #[test]
+    fn insurance_and_peek() {
+        table_tests::insurance_and_peek();
+    }
+
+    #[test]
+    fn european_original_bets_only() {
+        table_tests::european_original_bets_only();
+    }
+
+    #[test]
+    fn early_surrender_decisions() {
+        table_tests::early_surrender_decisions();
+    }
+
+    #[test]
+    fn basic_strategy_plays() {
+        strategy_tests::basic_strategy_plays();
+    }
+
+    #[test]
+    fn strategy_follows_rules() {
+        strategy_tests::strategy_follows_rules();
+    }
+
+    #[test]
+    fn scripted_and_bot_seats() {
+        input_tests::scripted_and_bot_seats();
+    }
+
+    #[test]
+    fn bot_bets_what_is_left() {
+        input_tests::bot_bets_what_is_left();
+    }
+
+    #[test]
+    fn partial_insurance() {
+        input_tests::partial_insurance();
+    }
+
+    #[test]
+    fn ace_nine_ace() {
+        hand_tests::ace_nine_ace();
+    }
+
+    #[test]
+    fn hand_properties() {
+        hand_tests::hand_properties();
+    }
+
+    #[test]
+    fn soft_17_sequences() {
+        round_tests::soft_17_sequences();
+    }
+
+    #[test]
+    fn dealer_draws_from_discards() {
+        round_tests::dealer_draws_from_discards();
+    }
+
+    #[test]
+    fn dealer_hits_soft_17_at_table() {
+        table_tests::dealer_hits_soft_17_at_table();
+    }
+
+    #[test]
+    fn dealer_odds() {
+        ev_tests::dealer_odds();
+    }
+
+    #[test]
+    fn compare_action_evs() {
+        ev_tests::compare_action_evs();
+    }
+
+    #[test]
+    fn track_mistakes() {
+        tracker_tests::track_mistakes();
+    }
+
+    #[test]
+    fn balanced_systems() {
+        count_tests::balanced_systems();
+    }
+
+    #[test]
+    fn count_a_round() {
+        count_tests::count_a_round();
+    }
+
+    #[test]
+    fn count_through_dry_shoe() {
+        count_tests::count_through_dry_shoe();
+    }
+
+    #[test]
+    fn format_timestamps() {
+        progress_tests::format_timestamps();
+    }
+
+    #[test]
+    fn drill_records() {
+        progress_tests::drill_records();
+    }
+
+    #[test]
+    fn generate_situations() {
+        training_tests::generate_situations();
+    }
+
+    #[test]
+    fn adaptive_weights() {
+        training_tests::adaptive_weights();
+    }
+
+    #[test]
+    fn bet_spreads() {
+        sim_tests::bet_spreads();
+    }
+
+    #[test]
+    fn sim_statistics() {
+        sim_tests::sim_statistics();
+    }
+
+    #[test]
+    fn simulate_strategies() {
+        sim_tests::simulate_strategies();
+    }
+
+    #[test]
+    fn reproducible_threads() {
+        sim_tests::reproducible_threads();
+    }
+
+    #[test]
+    fn validate_sim_options() {
+        sim_tests::validate_options();
+    }
+
+    #[test]
+    fn seeded_shoes() {
+        shoe_tests::seeded_shoes();
+    }
+
+    #[test]
+    fn shoe_runs_out() {
+        table_tests::shoe_runs_out();
+    }
+
+    #[test]
+    fn seeded_deals() {
+        table_tests::seeded_deals();
+    }
+
+    #[test]
+    fn event_lines() {
+        history_tests::event_lines();
+    }
+
+    #[test]
+    fn record_and_read_history() {
+        history_tests::record_and_read_history();
+    }
+
+    #[test]
+    fn replay_rounds() {
+        history_tests::replay_rounds();
+    }
+
+    #[test]
+    fn save_and_resume() {
+        save_tests::save_and_resume();
+    }
+
+    #[test]
+    fn create_profiles() {
+        profile_tests::create_profiles();
+    }
+
+    #[test]
+    fn record_profile_rounds() {
+        profile_tests::record_profile_rounds();
+    }
+
+    #[test]
+    fn rank_profiles() {
+        leaderboard_tests::rank_profiles();
+    }
+
+    #[test]
+    fn side_bet_outcomes() {
+        side_bet_tests::side_bet_outcomes();
+    }
+
+    #[test]
+    fn side_bet_paytables() {
+        side_bet_tests::side_bet_paytables();
+    }
+
+    #[test]
+    fn side_bet_house_edges() {
+        side_bet_tests::side_bet_house_edges();
+    }
+
+    #[test]
+    fn place_side_bets() {
+        player_tests::place_side_bets();
+    }
+
+    #[test]
+    fn side_bets_at_table() {
+        input_tests::side_bets_at_table();
+    }
+
+    #[test]
+    fn side_bets_after_dealer() {
+        table_tests::side_bets_after_dealer();
+    }
 }
@@ -1,16 +1,194 @@
-mod deck;
-mod player;
 mod io;
+mod replay;
+mod standings;
+mod trainer;
 
-use deck::Deck;
-use player::{ Player, PlayerList, Action, BetResult };
-use io::{ get_clamped_user_int, get_user_action, sleep };
+use blackjack::{
+    input, sim, Action, BasicStrategy, BetResult, BetSpread, Counter, CountSystem, Event, Header, HistoryWriter,
+    HoleCardRule, MistakeTracker, Phase, Player, PlayerInput, PlayerList, Profile, Profiles, SavedGame, Shoe,
+    SideBet, SideBetKind, SimOptions, SimResult, SimStrategy, Spot, Table, TableRules,
+};
+use io::{ get_clamped_user_int, get_user_confirmation, get_user_str, sleep, Stdin };
+use rand::Rng;
+use std::env;
+use std::fs::File;
+use std::io::{ stdout, BufWriter, Write };
+use std::time::{ Duration, Instant };
 
 const MAX_PLAYERS: u8 = 8;
 const CHIPS_CLAMP: [u32; 2] = [100, 1000];
 const MIN_BET_CLAMP: [u32; 2] = [10, 50];
+/// The chance of a count quiz before each round when quizzes are on.
+const QUIZ_CHANCE: f64 = 0.25;
+/// The names seats are saved under, in the order they are offered.
+const SEAT_KINDS: [&str; 3] = ["human", "coach", "bot"];
 
-fn init_game_options() -> (u8, u32, u32) {
+struct GameOptions {
+    /// How each seat is played, one of `SEAT_KINDS`.
+    seats: Vec<String>,
+    /// The profile in each seat, `None` for a guest.
+    names: Vec<Option<String>>,
+    /// The chips guests and new profiles start with.
+    starting_chips: u32,
+    min_bet: u32,
+    rules: TableRules,
+    count: Option<CountOptions>,
+    /// Seeds the shoe, so the same seed deals the same cards.
+    seed: u64,
+}
+
+/// The card counting trainer: the system to count and how to practise it.
+struct CountOptions {
+    system: CountSystem,
+    show: bool,
+    quiz: bool,
+}
+
+fn choose_rules() -> TableRules {
+    let presets = TableRules::presets();
+    let mut prompt = String::from("Which table rules?");
+    for (i, rules) in presets.iter().enumerate() {
+        prompt.push_str(&format!("\n{}: {}", i, rules));
+    }
+    prompt.push_str(&format!("\n{}: Load rules from a TOML file", presets.len()));
+    let choice = get_clamped_user_int::<usize>(Some(&prompt), 0, presets.len());
+    if choice < presets.len() {
+        return presets[choice].clone();
+    }
+    loop {
+        let path = get_user_str(Some("Path to the rules file:"));
+        match TableRules::load(path.trim()) {
+            Ok(rules) => return rules,
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// Offers the standard side bets at a table whose rules have none of their own.
+fn choose_side_bets(rules: &mut TableRules) {
+    if !rules.side_bets.is_empty() || !get_user_confirmation("Offer side bets?") {
+        return;
+    }
+    for kind in SideBetKind::ALL {
+        let side_bet = SideBet::standard(kind);
+        if get_user_confirmation(&format!("Offer {}?", side_bet)) {
+            rules.side_bets.push(side_bet);
+        }
+    }
+}
+
+fn seat_input(kind: &str) -> Box<dyn PlayerInput> {
+    match kind {
+        "human" => Box::new(Stdin { coach: false }),
+        "coach" => Box::new(Stdin { coach: true }),
+        _ => Box::new(BasicStrategy::default()),
+    }
+}
+
+fn choose_seats(names: &[Option<String>]) -> Vec<String> {
+    names.iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let question = match name {
+                Some(name) => format!("How does {} play seat {}?", name, i + 1),
+                None => format!("Who plays seat {}?", i + 1),
+            };
+            let prompt = format!(
+                "{}\n0: Human\n1: Human, flagging moves that differ from basic strategy\n2: Basic strategy bot",
+                question,
+            );
+            String::from(SEAT_KINDS[get_clamped_user_int::<usize>(Some(&prompt), 0, 2)])
+        })
+        .collect()
+}
+
+fn load_profiles() -> Profiles {
+    match Profiles::load(Profiles::PATH) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            println!("{}, starting without saved profiles", e);
+            Profiles::default()
+        },
+    }
+}
+
+/// Asks for a name until it makes a new profile, returning the name.
+fn create_profile(profiles: &mut Profiles, bankroll: u32) -> String {
+    loop {
+        let name = get_user_str(Some(&format!("What is the new profile's name? (max: {} characters)", Profiles::MAX_NAME)));
+        match profiles.create(&name, bankroll) {
+            Ok(profile) => return profile.name.clone(),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// Has each player join as a guest, a new profile or a saved one and pick a free seat,
+/// returning the profile in each seat. Profiles that are out of chips buy in again for
+/// `starting_chips`.
+fn choose_profiles(number_of_players: u8, starting_chips: u32) -> Vec<Option<String>> {
+    let mut profiles = load_profiles();
+    let mut names = vec![None; number_of_players as usize];
+    let mut free_seats: Vec<usize> = (0..names.len()).collect();
+    for n in 1..=number_of_players {
+        let available: Vec<Profile> = profiles.profiles.iter()
+            .filter(|p| !names.contains(&Some(p.name.clone())))
+            .cloned()
+            .collect();
+        let mut prompt = format!("Who is player {}?\n0: A guest\n1: A new profile", n);
+        for (i, profile) in available.iter().enumerate() {
+            prompt.push_str(&format!("\n{}: {}", i + 2, profile));
+        }
+        let name = match get_clamped_user_int::<usize>(Some(&prompt), 0, available.len() + 1) {
+            0 => None,
+            1 => Some(create_profile(&mut profiles, starting_chips)),
+            choice => Some(available[choice - 2].name.clone()),
+        };
+        let seat = match free_seats.len() {
+            1 => 0,
+            _ => {
+                let mut prompt = String::from("Which seat?");
+                for (i, seat) in free_seats.iter().enumerate() {
+                    prompt.push_str(&format!("\n{}: Seat {}", i, seat + 1));
+                }
+                get_clamped_user_int::<usize>(Some(&prompt), 0, free_seats.len() - 1)
+            },
+        };
+        names[free_seats.remove(seat)] = name;
+    }
+    for profile in profiles.profiles.iter_mut().filter(|p| names.contains(&Some(p.name.clone()))) {
+        if profile.bankroll == 0 {
+            println!("{} is out of chips and buys in again for {}", profile.name, starting_chips);
+            profile.bankroll = starting_chips;
+        }
+    }
+    if let Err(e) = profiles.save(Profiles::PATH) {
+        println!("{}", e);
+    }
+
+    names
+}
+
+fn choose_count() -> Option<CountOptions> {
+    let mut prompt = String::from("Count cards while you play?\n0: No");
+    for (i, system) in CountSystem::ALL.iter().enumerate() {
+        prompt.push_str(&format!("\n{}: Yes, with {}", i + 1, system));
+    }
+    match get_clamped_user_int::<usize>(Some(&prompt), 0, CountSystem::ALL.len()) {
+        0 => None,
+        n => Some(count_practice(CountSystem::ALL[n - 1])),
+    }
+}
+
+fn count_practice(system: CountSystem) -> CountOptions {
+    CountOptions {
+        system,
+        show: get_user_confirmation("Show the count after every round?"),
+        quiz: get_user_confirmation("Quiz me on the running count at random points?"),
+    }
+}
+
+fn init_game_options(seed: u64) -> GameOptions {
     let number_of_players = get_clamped_user_int::<u8>(
         Some(&format!("How many players? (max: {})", MAX_PLAYERS)),
         1, 
@@ -21,109 +199,419 @@ fn init_game_options() -> (u8, u32, u32) {
         CHIPS_CLAMP[0],
         CHIPS_CLAMP[1],
     );
+    let names = choose_profiles(number_of_players, starting_chips);
+    let seats = choose_seats(&names);
     let min_bet = get_clamped_user_int::<u32>(
         Some(&format!("What is the minimum bet? (min: {}, max: {})", MIN_BET_CLAMP[0], MIN_BET_CLAMP[1])), 
         MIN_BET_CLAMP[0],
         MIN_BET_CLAMP[1],
     );
+    let mut rules = choose_rules();
+    choose_side_bets(&mut rules);
+    let count = choose_count();
 
-    (number_of_players, starting_chips, min_bet)
+    GameOptions { seats, names, starting_chips, min_bet, rules, count, seed }
 }
 
-fn game_loop(options: (u8, u32, u32)) {
-    let (number_of_players, starting_chips, min_bet) = options;
-    let mut player_list = PlayerList::new(number_of_players, starting_chips);
-    let mut dealer = Player::new(0, 0);
-    let mut round = 0;
-    println!("Good luck!");
-    loop {
-        round += 1;
-        println!("\nRound {}", round);
-        for player in player_list.iter_mut().filter(|p| !p.is_broke()) {
-            println!("\n{} to bet, chips: {}", player, player.chips.unwrap());
-            let bet = match player.chips.unwrap() {
-                n if n <= min_bet => n,
-                _ => get_clamped_user_int(
-                    Some(&format!("How much would you like to bet? (minimum bet {})", min_bet)), 
-                    min_bet, 
-                    player.chips.unwrap(),
-                ),
+/// The options a saved game was started with, asking again only how to practise counting.
+fn resume_options(saved: &SavedGame) -> GameOptions {
+    GameOptions {
+        seats: saved.seats.clone(),
+        names: saved.names.iter().map(|name| Some(name.clone()).filter(|name| !name.is_empty())).collect(),
+        starting_chips: saved.starting_chips,
+        min_bet: saved.min_bet,
+        rules: saved.rules.clone(),
+        count: saved.counter.as_ref().map(|counter| count_practice(counter.system())),
+        seed: saved.seed,
+    }
+}
+
+fn hand_label(player: &Player, index: usize) -> String {
+    match player.hands.len() {
+        1 => format!("{}", player),
+        _ => format!("{} (hand {})", player, index + 1),
+    }
+}
+
+fn settlement_message(table: &Table, seat: usize, hand: usize, result: BetResult) -> String {
+    let player = &table.players()[seat];
+    let label = hand_label(player, hand);
+    match result {
+        BetResult::Blackjack(_) => format!("\nBlackjack for {}!", label),
+        BetResult::Win if table.rules().is_charlie(&player.hands[hand]) => format!("\n{} wins with a charlie!", label),
+        BetResult::Win => format!("\n{} wins!", label),
+        BetResult::StandOff => format!("\nStand-off for {}", label),
+        _ if table.dealer().has_blackjack() => format!("\n{} loses to the dealer's blackjack", label),
+        _ => format!("\n{} loses", label),
+    }
+}
+
+/// What the game says about `event`, if anything. `last_action` remembers the action a
+/// dealt card answers.
+fn describe(table: &Table, event: &Event, last_action: &mut Option<Action>) -> Option<String> {
+    let players = table.players();
+    let message = match *event {
+        Event::RoundStarted { round } => format!("\nRound {}", round),
+        Event::Shuffled => String::from("The cut card is out, shuffling the shoe"),
+        Event::ShoeRefilled => String::from("The shoe has run dry, shuffling the discards back in"),
+        Event::PlayerCard { seat, hand, ref card, total } => match last_action.take() {
+            Some(Action::Split) => {
+                let mut message = format!(
+                    "You split into {} hands, this hand gets the {} (total: {})",
+                    players[seat].hands.len(),
+                    card,
+                    total,
+                );
+                if players[seat].hands[hand].is_split_aces() {
+                    message.push_str("\nSplit aces receive one card only");
+                }
+                message
+            },
+            Some(_) => format!("You get the {} (total: {})", card, total),
+            None if players[seat].hands[hand].is_split_aces() => {
+                format!("{} gets the {} (total: {})", hand_label(&players[seat], hand), card, total)
+            },
+            None => return None,
+        },
+        Event::SideBetSettled { seat, kind, outcome: Some(outcome), payout } => {
+            format!("{} pays {} {} chips ({})", kind, players[seat], payout, outcome)
+        },
+        Event::SideBetSettled { seat, kind, outcome: None, .. } => format!("{} loses the {} bet", players[seat], kind),
+        Event::InsuranceOpen => String::from("\nDealer shows an Ace, insurance is open"),
+        Event::InsuranceTaken { seat, amount } => format!("{} takes {} chips of insurance", players[seat], amount),
+        Event::EvenMoneyPaid { seat, .. } => format!("{} takes even money", players[seat]),
+        Event::Surrendered { seat, hand, .. } => format!("{} surrenders", hand_label(&players[seat], hand)),
+        Event::DealerPeeked { blackjack: true } => String::from("\nDealer checks the hole card: blackjack!"),
+        Event::DealerPeeked { blackjack: false } => String::from("\nDealer checks the hole card: no blackjack"),
+        Event::InsuranceSettled { seat, payout: 0 } => format!("{} loses their insurance", players[seat]),
+        Event::InsuranceSettled { seat, payout } => format!("Insurance pays {} {} chips", players[seat], payout),
+        Event::TurnStarted { seat, hand } => {
+            let player = &players[seat];
+            let cards = format!("{} cards: {}", hand_label(player, hand), player.hands[hand]);
+            match hand {
+                0 => format!("\n{}'s turn:\nDealer upcard: {}\n{}", player, table.dealer().hand().cards[0], cards),
+                _ => cards,
+            }
+        },
+        Event::ActionTaken { seat, hand, ref action } => {
+            *last_action = match action {
+                Action::Stand | Action::Surrender => None,
+                _ => Some(action.clone()),
             };
-            player.bet(bet).unwrap();
+            format!("{} chooses to {}", hand_label(&players[seat], hand), action)
+        },
+        Event::HandBusted { .. } => String::from("You went bust!\n"),
+        Event::Charlie { seat, hand } => format!("{}-card charlie!", players[seat].hands[hand].cards.len()),
+        Event::HoleCardRevealed { ref card, total } => format!("\nDealer shows the {} (total: {})", card, total),
+        Event::DealerCard { ref card, total } => {
+            let mut message = format!("Dealer gets the {} (total: {})", card, total);
+            let dealer = table.dealer();
+            if table.rules().hole_card == HoleCardRule::European && dealer.has_blackjack() {
+                message.push_str("\nDealer has blackjack, players lose their original bets only");
+            }
+            message
+        },
+        Event::DealerBusts { .. } => String::from("Dealer busts!"),
+        Event::HandSettled { seat, hand, result, .. } => settlement_message(table, seat, hand, result),
+        Event::OriginalBetsOnly { seat, refund: 0 } => format!("\n{} loses", players[seat]),
+        Event::OriginalBetsOnly { seat, refund } => {
+            format!("\n{} loses, {} chips from doubles and splits are returned", players[seat], refund)
+        },
+        _ => return None,
+    };
+
+    Some(message)
+}
+
+/// Prints what happened at the table, pausing after dealt cards and payouts.
+fn render(table: &Table, events: Vec<Event>, last_action: &mut Option<Action>) {
+    for event in events {
+        let pause = match event {
+            Event::PlayerCard { .. } => last_action.is_some(),
+            Event::DealerCard { .. } | Event::HandSettled { .. } | Event::SideBetSettled { .. } => true,
+            _ => false,
+        };
+        if let Some(message) = describe(table, &event, last_action) {
+            println!("{}", message);
+        }
+        if pause {
+            sleep(1);
         }
-        let mut deck = Deck::new();
-        deck.shuffle();
-        dealer.get_cards(&mut deck, 2);
-        for player in player_list.iter_mut().filter(|p| !p.is_broke()) {
-            player.get_cards(&mut deck, 2);
-            println!("\n{}'s turn:", player);
-            println!("Your cards: {}, {} (total: {})", player.hand[0], player.hand[1], player.hand_total());
-            println!("Dealer upcard: {}", dealer.hand[0]);
-            loop {
-                match get_user_action(player) {
-                    Action::Hit => {
-                        player.get_cards(&mut deck, 1);
-                        println!("You get the {} (total: {})", player.latest_card(), player.hand_total());
-                        if player.hand_total() > 21 {
-                            println!("You went bust!\n");
-                            player.resolve_bet(BetResult::Lose).unwrap();
-                            break;
-                        }
-                    },
-                    Action::Stand => break,
-                    Action::Surrender => {
-                        player.resolve_bet(BetResult::Surrender).unwrap();
-                        break;
-                    },
-                    Action::DoubleDown => { 
-                        player.double_down();
-                        player.get_cards(&mut deck, 1);
-                        println!("You get the {}", player.latest_card());
-                    },
+    }
+}
+
+/// Asks for the running count, returning whether the answer was right.
+fn quiz_count(counter: &Counter) -> bool {
+    let answer = get_clamped_user_int::<i32>(Some("\nCount quiz! What is the running count?"), -500, 500);
+    match answer == counter.running() {
+        true => println!("Correct!"),
+        false => println!("Not quite, the running count is {:+}", counter.running()),
+    }
+
+    answer == counter.running()
+}
+
+/// Carries on the hand history at `path` for a resumed game, or starts a new one,
+/// returning the writer and the file's path.
+fn open_history(path: Option<String>, header: &Header) -> Option<(HistoryWriter<BufWriter<File>>, String)> {
+    let opened = match path {
+        Some(path) => HistoryWriter::append(&path).map(|writer| (writer, path)),
+        None => HistoryWriter::create(HistoryWriter::DIR, header),
+    };
+    match opened {
+        Ok((writer, path)) => {
+            println!("Recording the hand history to {}", path);
+            Some((writer, path))
+        },
+        Err(e) => {
+            println!("{}, playing without a hand history", e);
+            None
+        },
+    }
+}
+
+/// Plays rounds until every player is out of chips, saving the game to `save_path` and
+/// the profiles at the table after each one. A `saved` game picks up where it stopped.
+fn game_loop(options: GameOptions, saved: Option<SavedGame>, save_path: &str) {
+    let GameOptions { seats: seat_kinds, names, starting_chips, min_bet, rules, count, seed } = options;
+    let mut seats: Vec<Box<dyn PlayerInput>> = seat_kinds.iter().map(|kind| seat_input(kind)).collect();
+    let mut profiles = load_profiles();
+    let chips: Vec<u32> = names.iter()
+        .map(|name| name.as_ref().and_then(|name| profiles.get(name)).map_or(starting_chips, |p| p.bankroll))
+        .collect();
+    let header = Header::new(seed, chips.clone(), min_bet, rules.clone());
+    let (mut table, mut counter, history_path) = match saved {
+        Some(saved) => (saved.table(), saved.counter, saved.history),
+        None => {
+            let mut players = PlayerList::from_chips(&chips);
+            for (player, name) in players.iter_mut().zip(names.iter()) {
+                player.name = name.clone();
+            }
+            let counter = count.as_ref().map(|c| Counter::new(c.system, rules.decks));
+            let shoe = Shoe::seeded(rules.decks, rules.penetration, seed);
+            (Table::with_shoe(rules, players, min_bet, shoe), counter, None)
+        },
+    };
+    let mut tracker = MistakeTracker::new();
+    let mut quizzes = (0, 0);
+    let mut last_action = None;
+    println!("\n{}", table.rules());
+    println!("Seed: {} (start with --seed {} to deal the same cards again)", seed, seed);
+    let (mut history, mut history_path) = match open_history(history_path, &header) {
+        Some((writer, path)) => (Some(writer), Some(path)),
+        None => (None, None),
+    };
+    let mut autosave = true;
+    let mut save_profiles = names.iter().any(Option::is_some);
+    profiles.start_sessions(&table);
+    println!("Saving the game to {} after every round (start with --load {} to carry on)", save_path, save_path);
+    println!("Good luck!");
+    'game: while table.players_left() {
+        if let (Some(options), Some(counter)) = (&count, &counter) {
+            if options.quiz && table.round() > 0 && rand::thread_rng().gen_bool(QUIZ_CHANCE) {
+                quizzes.0 += quiz_count(counter) as u32;
+                quizzes.1 += 1;
+            }
+        }
+        let chips_before: Vec<u32> = table.players().iter().map(|p| p.chips.unwrap_or(0)).collect();
+        let mut round_events = Vec::new();
+        let mut events = table.start_round().unwrap();
+        loop {
+            if let Some(counter) = counter.as_mut() {
+                events.iter().for_each(|e| counter.observe_event(e));
+            }
+            if let Some(Err(e)) = history.as_mut().map(|h| h.record(&table, &events)) {
+                println!("{}, no longer recording the hand history", e);
+                history = None;
+                history_path = None;
+            }
+            round_events.extend(events.iter().cloned());
+            render(&table, events, &mut last_action);
+            if table.phase() == Phase::RoundOver {
+                break;
+            }
+            let spot = Spot::at(&table);
+            events = match input::step(&mut table, &mut seats) {
+                Ok(events) => events,
+                Err(e) => {
+                    println!("{}, ending the game", e);
+                    break 'game;
+                },
+            };
+            if let Some(spot) = spot {
+                if let Some(Event::ActionTaken { action, .. }) = events.iter().find(|e| matches!(e, Event::ActionTaken { .. })) {
+                    tracker.record(spot, table.rules(), action.clone());
                 }
-                sleep(1);
             }
         }
-        println!("\nDealer shows the {} (total: {})", dealer.hand[1], dealer.hand_total());
-        while dealer.hand_total() < 17 {
-            dealer.get_cards(&mut deck, 1);
-            println!("Dealer gets the {} (total: {})", dealer.latest_card(), dealer.hand_total());
-            if dealer.hand_total() > 21 {
-                println!("Dealer busts!");
+        if autosave {
+            let saved = SavedGame {
+                counter: counter.clone(),
+                history: history_path.clone(),
+                ..SavedGame::new(&table, seed, starting_chips, seat_kinds.clone())
+            };
+            if let Err(e) = saved.save(save_path) {
+                println!("{}, no longer saving the game", e);
+                autosave = false;
             }
-            sleep(1);
         }
-        for player in player_list.iter_mut().filter(|p| p.is_in_pot()) {
-            match dealer.hand_total() {
-                _ if !dealer.has_blackjack() && player.has_blackjack() => {
-                    println!("\nBlackjack for {}!", player);
-                    player.resolve_bet(BetResult::Blackjack).unwrap();
-                }
-                n if n > 21 || n < player.hand_total() => {
-                    println!("\n{} wins!", player);
-                    player.resolve_bet(BetResult::Win).unwrap();
-                },
-                n if n == player.hand_total() => {
-                    println!("\nStand-off for {}", player);
-                    player.resolve_bet(BetResult::StandOff).unwrap();
-                },
-                _ => {
-                    println!("\n{} loses", player);
-                    player.resolve_bet(BetResult::Lose).unwrap();
-                },
+        if save_profiles {
+            profiles.record_round(&table, &chips_before, &round_events);
+            if let Err(e) = profiles.save(Profiles::PATH) {
+                println!("{}, no longer saving profiles", e);
+                save_profiles = false;
             }
-            sleep(1);
         }
-        player_list.clear_cards();
-        dealer.hand = Vec::new();
-        if !player_list.players_left() { 
-            break;
+        if let (Some(options), Some(counter)) = (&count, &counter) {
+            if options.show {
+                println!(
+                    "\n{} running count: {:+}, true count: {:+.1}",
+                    counter.system(),
+                    counter.running(),
+                    counter.true_count(table.shoe()),
+                );
+            }
         }
     }
+    println!("\nHow everyone played against basic strategy:");
+    for report in tracker.reports(table.players()) {
+        println!("\n{}", report);
+    }
+    if quizzes.1 > 0 {
+        println!("\nCount quizzes: {}/{} correct", quizzes.0, quizzes.1);
+    }
+    for profile in names.iter().flatten().filter_map(|name| profiles.get(name)) {
+        println!("\n{} lifetime: {}", profile.name, profile.stats);
+    }
     println!("\nThanks for playing!");
 }
 
+const USAGE: &str = "Usage: blackjack [--seed N|--load [FILE]|drill|train|simulate [--hands N] [--rules PRESET|FILE] [--strategy NAME] \
+    [--spread 1,2,4] [--seed N] [--threads N] [--margin UNITS]|replay FILE [--round N] [--seat N] [--verify]\
+    |leaderboard [--by net|roi|peak|streak] [--from DATE] [--to DATE] [--rules NAME]|sessions NAME [--from DATE] [--to DATE] [--rules NAME]]";
+
+/// Reads the `simulate` flags, starting from a million flat bet hands of basic strategy
+/// on the Vegas Strip with a random seed, on every core.
+fn parse_sim_options(args: &[String]) -> Result<SimOptions, String> {
+    let mut options = SimOptions {
+        rules: TableRules::vegas_strip(),
+        hands: 1_000_000,
+        strategy: SimStrategy::Basic,
+        spread: BetSpread::flat(),
+        seed: rand::thread_rng().gen(),
+        threads: 0,
+        target_margin: None,
+    };
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--hands" => options.hands = value.parse().map_err(|_| format!("'{}' is not a number of hands", value))?,
+            "--rules" => {
+                let preset = TableRules::presets().into_iter()
+                    .find(|r| r.name.to_lowercase().replace(' ', "-") == value.to_lowercase());
+                options.rules = match preset {
+                    Some(rules) => rules,
+                    None => TableRules::load(value).map_err(|e| e.to_string())?,
+                };
+            },
+            "--strategy" => {
+                options.strategy = SimStrategy::ALL.into_iter()
+                    .find(|s| s.slug() == value)
+                    .ok_or(format!("'{}' is not one of basic, mimic-dealer or never-bust", value))?;
+            },
+            "--spread" => {
+                options.spread = BetSpread::parse(value)
+                    .map_err(|_| format!("'{}' is not a bet spread such as 1,2,4,8", value))?;
+            },
+            "--seed" => options.seed = value.parse().map_err(|_| format!("'{}' is not a seed", value))?,
+            "--threads" => options.threads = value.parse().map_err(|_| format!("'{}' is not a number of threads", value))?,
+            "--margin" => {
+                let margin = value.parse().map_err(|_| format!("'{}' is not a margin in units", value))?;
+                options.target_margin = Some(margin);
+            },
+            _ => return Err(format!("Unknown option {}", flag)),
+        }
+    }
+    options.validate().map_err(|e| e.to_string())?;
+
+    Ok(options)
+}
+
+fn simulate(args: &[String]) {
+    let options = match parse_sim_options(args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}\n{}", e, USAGE);
+            return;
+        },
+    };
+    println!("Simulating {} hands of {}, betting {}", options.hands, options.strategy, options.spread);
+    println!("{}", options.rules);
+    println!("Seed: {}\n", options.seed);
+    let mut last_update = Instant::now();
+    let progress = |result: &SimResult| {
+        if last_update.elapsed() >= Duration::from_millis(250) {
+            print!("\r{} hands, EV per hand {:+.4} ± {:.4} units", result.hands, result.ev(), result.ev_margin());
+            stdout().flush().unwrap();
+            last_update = Instant::now();
+        }
+    };
+    match sim::simulate(&options, progress) {
+        Ok(result) => println!("\r\n{}", result),
+        Err(e) => println!("\n{}", e),
+    }
+}
+
+/// The seed given with `--seed`, or a random one.
+fn parse_seed(args: &[String]) -> Result<u64, String> {
+    match args {
+        [] => Ok(rand::thread_rng().gen()),
+        [flag, value] if flag == "--seed" => value.parse().map_err(|_| format!("'{}' is not a seed", value)),
+        _ => Err(String::from("--seed needs a value")),
+    }
+}
+
 fn main() {
-    println!("Welcome to blackjack!");
-    game_loop(init_game_options())
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("--seed") => match parse_seed(&args) {
+            Ok(seed) => {
+                println!("Welcome to blackjack!");
+                game_loop(init_game_options(seed), None, SavedGame::PATH)
+            },
+            Err(e) => println!("{}\n{}", e, USAGE),
+        },
+        Some("--load") => {
+            let path = args.get(1).map_or(SavedGame::PATH, String::as_str);
+            match SavedGame::load(path) {
+                Ok(saved) if saved.chips.iter().all(|&chips| chips == 0) => {
+                    println!("Every player in {} is out of chips, start a new game instead", path);
+                },
+                Ok(saved) => {
+                    println!("Welcome back to blackjack! Carrying on from round {}", saved.round + 1);
+                    game_loop(resume_options(&saved), Some(saved), path)
+                },
+                Err(e) => println!("{}", e),
+            }
+        },
+        Some("drill") => trainer::drill(),
+        Some("train") => trainer::train(),
+        Some("simulate") => simulate(&args[1..]),
+        Some("replay") => match replay::parse_options(&args[1..]) {
+            Ok(options) => replay::replay(options),
+            Err(e) => println!("{}\n{}", e, USAGE),
+        },
+        Some("leaderboard") => {
+            if let Err(e) = standings::leaderboard(&args[1..]) {
+                println!("{}\n{}", e, USAGE);
+            }
+        },
+        Some("sessions") => {
+            if let Err(e) = standings::sessions(&args[1..]) {
+                println!("{}\n{}", e, USAGE);
+            }
+        },
+        Some(_) => println!("{}", USAGE),
+    }
 }
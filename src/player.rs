@@ -1,141 +1,366 @@
-use crate::deck::{ Card, Deck };
-use std::{ fmt, cmp, slice };
-
-#[derive(Clone, PartialEq)]
+use crate::deck::Card;
+use crate::error::{ Error, Result };
+use crate::hand::Hand;
+use crate::rules::{ BlackjackPayout, SurrenderRule, TableRules };
+use crate::shoe::Shoe;
+use crate::side_bet::SideBetKind;
+use std::{ fmt, cmp, ops, slice };
+
+/// A decision a player can make on their turn.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Action {
     Hit,
     Stand,
     Surrender,
     DoubleDown,
+    Split,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::Hit => "Hit",
+            Action::Stand => "Stand",
+            Action::Surrender => "Surrender",
+            Action::DoubleDown => "Double Down",
+            Action::Split => "Split",
+        };
+        write!(f, "{}", name)
+    }
 }
 
+/// The outcome used to settle a hand's bet.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BetResult {
     Win,
     Lose,
     Surrender,
     StandOff,
-    Blackjack,
+    Blackjack(BlackjackPayout),
+    EvenMoney,
 }
 
+/// A seat at the table, or the dealer when created without chips.
+#[derive(Clone)]
 pub struct Player {
-    pub hand: Vec<Card>,
+    pub hands: Vec<Hand>,
     pub chips: Option<u32>,
+    /// The profile sitting in this seat, shown in place of "Player N".
+    pub name: Option<String>,
     number: u8,
-    pot: Option<u32>,
+    active: usize,
+    insurance: u32,
+    side_bets: Vec<(SideBetKind, u32)>,
+    /// The bet placed before the deal, and everything staked on top of it this round.
+    original_bet: u32,
+    wagered: u32,
 }
 
 impl Player {
+    /// Creates player `number`; zero starting chips makes a dealer, who never bets.
     pub fn new(starting_chips: u32, number: u8) -> Player {
         Player { 
-            hand: Vec::new(), 
+            hands: vec![Hand::new()], 
             chips: if starting_chips > 0 { Some(starting_chips) } else { None }, 
+            name: None,
             number,
-            pot: if starting_chips > 0 { Some(0) } else { None },
+            active: 0,
+            insurance: 0,
+            side_bets: Vec::new(),
+            original_bet: 0,
+            wagered: 0,
+        }
+    }
+
+    pub fn hand(&self) -> &Hand {
+        &self.hands[self.active]
+    }
+
+    pub fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hands[self.active]
+    }
+
+    /// Index of the hand currently being played, starting from 0.
+    pub fn active_hand(&self) -> usize {
+        self.active
+    }
+
+    /// Moves play on to the next split hand, returning false once every hand is done.
+    pub fn next_hand(&mut self) -> bool {
+        if self.active + 1 < self.hands.len() {
+            self.active += 1;
+            return true;
         }
+
+        false
     }
 
-    pub fn get_cards(&mut self, deck: &mut Deck, n: usize) {
-        self.hand.extend(deck.deal(n));
+    pub fn get_cards(&mut self, shoe: &mut Shoe, n: usize) -> Result<()> {
+        self.hand_mut().cards.extend(shoe.deal(n)?);
+
+        Ok(())
     }
 
-    pub fn valid_moves(&self) -> Vec<Action> {
-        assert!(self.hand_total() <= 21, "Tried to find moves for a busted player");
+    pub fn valid_moves(&self, rules: &TableRules) -> Vec<Action> {
+        assert!(!self.hand().is_busted(), "Tried to find moves for a busted player");
         let mut valid_moves = vec![Action::Hit, Action::Stand];
-        if self.hand.len() == 2 {
-            valid_moves.push(Action::Surrender);
-            let double_down_range = 9..=11;
-            if self.chips >= self.pot && double_down_range.contains(&self.hand_total()) {
+        let hand = self.hand();
+        if hand.cards.len() == 2 {
+            let can_afford = self.chips >= self.pot();
+            if rules.surrender != SurrenderRule::NotAllowed && !hand.is_split() {
+                valid_moves.push(Action::Surrender);
+            }
+            let can_double = rules.double.allows(hand.total())
+                && (!hand.is_split() || rules.double_after_split);
+            if can_afford && can_double {
                 valid_moves.push(Action::DoubleDown);
             }
+            if can_afford && hand.is_pair() && self.hands.len() < rules.max_split_hands {
+                valid_moves.push(Action::Split);
+            }
         }
 
         valid_moves
     }
 
-    fn ace_count(&self) -> u8 {
-        self.hand.iter()
-            .map(|x| x.numeric_value())
-            .filter(|&x| x == 11)
-            .count() as u8
-    }
-
     pub fn latest_card(&self) -> &Card {
-        &self.hand[self.hand.len()-1]
+        let cards = &self.hand().cards;
+        &cards[cards.len()-1]
     }
 
     pub fn hand_total(&self) -> u8 {
-        let base_value: u8 = self.hand.iter()
-            .map(|x| x.numeric_value())
-            .sum();
+        self.hand().total()
+    }
 
-        if base_value <= 21 { return base_value }
+    pub fn pot(&self) -> Option<u32> {
+        self.chips.map(|_| self.hand().pot)
+    }
+
+    pub fn bet(&mut self, amount: u32) -> Result<u32> {
+        self.stake(amount)?;
+        self.original_bet += amount;
+
+        Ok(amount)
+    }
 
-        let ace_reduction = cmp::min(base_value / 10 + 1, self.ace_count()) * 10;
-        base_value - ace_reduction
+    pub fn double_down(&mut self) {
+        self.stake(self.hand().pot).unwrap();
+        self.hand_mut().doubled = true;
     }
 
-    pub fn bet(&mut self, amount: u32) -> Result<u32, &str> {
+    fn stake(&mut self, amount: u32) -> Result<u32> {
         match self.chips {
-            Some(n) if amount > n => return Err("Program tried to bet more chips than it has"),
-            None => return Err("Program tried to bet as a dealer"),
+            Some(n) if amount > n => return Err(Error::InsufficientChips),
+            None => return Err(Error::DealerBet),
             _ => (),
         }
         if let Some(chips) = self.chips.as_mut() {
             *chips -= amount;
         }
-        if let Some(pot) = self.pot.as_mut() {
-            *pot += amount;
-        } 
+        self.hand_mut().pot += amount;
+        self.wagered += amount;
 
         Ok(amount)
     }
 
-    pub fn double_down(&mut self) {
-        self.bet(self.pot.unwrap()).unwrap();
+    /// Splits the active pair into two hands, staking the original bet again on the new one.
+    pub fn split(&mut self) -> Result<u32> {
+        if !self.hand().is_pair() {
+            return Err(Error::NotAPair);
+        }
+        let amount = self.hand().pot;
+        match self.chips {
+            Some(n) if amount > n => return Err(Error::InsufficientChips),
+            None => return Err(Error::DealerBet),
+            _ => (),
+        }
+        if let Some(chips) = self.chips.as_mut() {
+            *chips -= amount;
+        }
+        self.wagered += amount;
+        let hand = self.hand_mut();
+        hand.split = true;
+        let second_card = hand.cards.pop().unwrap();
+        self.hands.insert(
+            self.active + 1,
+            Hand { cards: vec![second_card], pot: amount, split: true, doubled: false },
+        );
+
+        Ok(amount)
     }
 
-    pub fn resolve_bet(&mut self, result: BetResult) -> Result<u32, &str> {
-        match self.pot {
-            Some(n) if n <= 0 => return Err("Tried to resolve when no bet was made"),
-            None => return Err("Tried to resolve bet on a dealer"),
-            _ => (),
+    pub fn resolve_bet(&mut self, result: BetResult) -> Result<u32> {
+        self.resolve_hand(self.active, result)
+    }
+
+    pub fn resolve_hand(&mut self, index: usize, result: BetResult) -> Result<u32> {
+        let pot = match self.chips {
+            None => return Err(Error::DealerBet),
+            _ => self.hands[index].pot,
+        };
+        if pot == 0 {
+            return Err(Error::NoBet);
         }
         let amount = match result {
-            BetResult::Win => self.pot.unwrap() * 2,
+            BetResult::Win => pot * 2,
             BetResult::Lose => 0,
-            BetResult::Surrender => self.pot.unwrap() / 2,
-            BetResult::Blackjack => (self.pot.unwrap() as f32 * 2.5) as u32,
-            BetResult::StandOff => self.pot.unwrap(),
+            BetResult::Surrender => pot / 2,
+            BetResult::Blackjack(payout) => payout.pay(pot),
+            BetResult::StandOff => pot,
+            BetResult::EvenMoney => pot * 2,
         };
         if let Some(chips) = self.chips.as_mut() {
             *chips += amount;
         }
-        if let Some(pot) = self.pot.as_mut() {
-            *pot = 0;
+        self.hands[index].pot = 0;
+
+        Ok(amount)
+    }
+
+    /// Settles a loss to a dealer blackjack under the "original bets only" rule: the original
+    /// bet is lost and any chips added by doubling or splitting are returned. Chips already
+    /// lost on busted split hands count towards the original bet.
+    pub fn lose_original_bet(&mut self) -> Result<u32> {
+        if self.chips.is_none() {
+            return Err(Error::DealerBet);
+        }
+        let at_risk: u32 = self.hands.iter().map(|h| h.pot).sum();
+        if at_risk == 0 {
+            return Err(Error::NoBet);
+        }
+        let already_lost = self.wagered - at_risk;
+        let refund = at_risk - self.original_bet.saturating_sub(already_lost).min(at_risk);
+        if let Some(chips) = self.chips.as_mut() {
+            *chips += refund;
+        }
+        for hand in self.hands.iter_mut() {
+            hand.pot = 0;
+        }
+
+        Ok(refund)
+    }
+
+    pub fn insurance(&self) -> u32 {
+        self.insurance
+    }
+
+    /// The largest insurance stake allowed: half the original bet, limited by the chips left.
+    pub fn max_insurance(&self) -> u32 {
+        match self.chips {
+            Some(n) => cmp::min(self.hands[0].pot / 2, n),
+            None => 0,
+        }
+    }
+
+    /// Places an insurance side bet, kept apart from the main wager in the pot.
+    pub fn insure(&mut self, amount: u32) -> Result<u32> {
+        match self.chips {
+            None => return Err(Error::DealerBet),
+            Some(n) if amount > n => return Err(Error::InsufficientChips),
+            _ => (),
+        }
+        if amount + self.insurance > self.hands[0].pot / 2 {
+            return Err(Error::InsuranceTooLarge);
+        }
+        if let Some(chips) = self.chips.as_mut() {
+            *chips -= amount;
+        }
+        self.insurance += amount;
+
+        Ok(amount)
+    }
+
+    /// Settles the insurance bet, which pays 2:1 when the dealer has blackjack.
+    pub fn resolve_insurance(&mut self, dealer_blackjack: bool) -> Result<u32> {
+        if self.insurance == 0 {
+            return Err(Error::NoInsurance);
+        }
+        let amount = if dealer_blackjack { self.insurance * 3 } else { 0 };
+        if let Some(chips) = self.chips.as_mut() {
+            *chips += amount;
+        }
+        self.insurance = 0;
+
+        Ok(amount)
+    }
+
+    /// The side bets staked this round and not yet settled.
+    pub fn side_bets(&self) -> &[(SideBetKind, u32)] {
+        &self.side_bets
+    }
+
+    /// Stakes `amount` on a side bet of `kind`, kept apart from the main wager like
+    /// insurance. Each side bet can be up to the main bet.
+    pub fn place_side_bet(&mut self, kind: SideBetKind, amount: u32) -> Result<u32> {
+        match self.chips {
+            None => return Err(Error::DealerBet),
+            Some(n) if amount > n => return Err(Error::InsufficientChips),
+            _ => (),
+        }
+        let staked: u32 = self.side_bets.iter().filter(|(k, _)| *k == kind).map(|(_, n)| n).sum();
+        if amount + staked > self.hands[0].pot {
+            return Err(Error::SideBetTooLarge);
+        }
+        if let Some(chips) = self.chips.as_mut() {
+            *chips -= amount;
+        }
+        match self.side_bets.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, stake)) => *stake += amount,
+            None => self.side_bets.push((kind, amount)),
         }
 
         Ok(amount)
     }
 
+    /// Settles the side bet of `kind`, paying back `payout` chips. Long-odds payouts can be
+    /// huge, so the chips stop at the most a bankroll can hold.
+    pub fn resolve_side_bet(&mut self, kind: SideBetKind, payout: u32) -> Result<u32> {
+        let index = self.side_bets.iter().position(|(k, _)| *k == kind).ok_or(Error::NoBet)?;
+        self.side_bets.remove(index);
+        if let Some(chips) = self.chips.as_mut() {
+            *chips = chips.saturating_add(payout);
+        }
+
+        Ok(payout)
+    }
+
     pub fn is_in_pot(&self) -> bool {
-        self.pot.unwrap() > 0
+        self.hands.iter().any(|h| h.pot > 0)
     }
 
     pub fn has_blackjack(&self) -> bool {
-        self.hand_total() == 21 && self.hand.len() == 2
+        self.hand().is_natural()
     }
 
     pub fn is_broke(&self) -> bool {
         self.chips.unwrap() == 0
     }
+
+    /// Empties every hand, handing the cards back so they can be discarded.
+    pub fn clear_cards(&mut self) -> Vec<Card> {
+        let cards = self.hands.drain(..)
+            .flat_map(|h| h.cards)
+            .collect();
+        self.hands = vec![Hand::new()];
+        self.active = 0;
+        self.original_bet = 0;
+        self.wagered = 0;
+
+        cards
+    }
 }
 
 impl fmt::Display for Player {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Player {}", self.number)
+        match &self.name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "Player {}", self.number),
+        }
     }
 }
 
+/// Every seat at the table, numbered from 1.
+#[derive(Clone)]
 pub struct PlayerList(Vec<Player>);
 
 impl PlayerList {
@@ -148,27 +373,71 @@ impl PlayerList {
         player_list
     }
 
-    pub fn iter_mut(&mut self) -> slice::IterMut<Player> {
+    /// Seats a player for each entry of `chips`, with that many chips.
+    pub fn from_chips(chips: &[u32]) -> PlayerList {
+        let mut player_list = PlayerList::new(chips.len() as u8, 1);
+        for (player, &chips) in player_list.iter_mut().zip(chips) {
+            player.chips = Some(chips);
+        }
+
+        player_list
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Player> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Player> {
         self.0.iter_mut()
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn players_left(&self) -> bool {
         self.0.iter()
             .filter(|p| !p.is_broke())
             .count() > 0
     }
 
-    pub fn clear_cards(&mut self) {
-        for player in self.iter_mut() {
-            player.hand = Vec::new();
-        }
+    pub fn clear_cards(&mut self) -> Vec<Card> {
+        self.iter_mut()
+            .flat_map(|p| p.clear_cards())
+            .collect()
     }
 }
 
 
+impl ops::Index<usize> for PlayerList {
+    type Output = Player;
+
+    fn index(&self, seat: usize) -> &Player {
+        &self.0[seat]
+    }
+}
+
+impl ops::IndexMut<usize> for PlayerList {
+    fn index_mut(&mut self, seat: usize) -> &mut Player {
+        &mut self.0[seat]
+    }
+}
+
+
+#[cfg(test)]
 #[allow(unused)]
 pub mod tests {
     use super::*;
+    use crate::deck::Deck;
+    use crate::rules::DoubleRule;
+
+    fn split_rules(max_split_hands: usize) -> TableRules {
+        TableRules { max_split_hands, ..TableRules::vegas_strip() }
+    }
 
     pub fn create_player() {
         let mut player = Player::new(20, 1);
@@ -180,49 +449,79 @@ pub mod tests {
         assert_eq!(player.chips, Some(40));
     }
 
+    pub fn name_player() {
+        let mut player = Player::new(100, 2);
+        player.name = Some(String::from("Ada"));
+        assert_eq!(format!("{}", player), "Ada");
+        player.name = None;
+        assert_eq!(format!("{}", player), "Player 2");
+    }
+
     pub fn deal_player_cards() {
         let mut player = Player::new(20, 0);
-        let mut deck = Deck::new();
-        player.get_cards(&mut deck, 3);
-        assert_eq!(player.hand.len(), 3);
-        assert_eq!(format!("{}", player.hand[0]), "2 of Diamonds");
+        let mut shoe = Shoe::stacked(Deck::new());
+        player.get_cards(&mut shoe, 3);
+        assert_eq!(player.hand().cards.len(), 3);
+        assert_eq!(format!("{}", player.hand().cards[0]), "2 of Diamonds");
         assert_eq!(player.hand_total(), 9);
         let test_vals: [u8; 5] = [17, 21, 18, 26, 27];
         for n in 0..5 {
             let hard_hand = Card::_test_hand(n);
-            player.hand = hard_hand;
+            player.hand_mut().cards = hard_hand;
             assert_eq!(player.hand_total(), test_vals[n as usize]);
         }
         assert_eq!(player.latest_card(), &Card::_last_card())
     }
 
     pub fn check_valid_moves() {
+        let rules = TableRules { double: DoubleRule::NineToEleven, ..TableRules::vegas_strip() };
         let mut player = Player::new(500, 0);
-        let mut deck = Deck::new();
-        deck.deal(2);
-        player.get_cards(&mut deck, 2);
-        assert!(player.valid_moves().contains(&Action::Hit));
-        assert!(player.valid_moves().contains(&Action::Stand));
-        assert!(player.valid_moves().contains(&Action::Surrender));
-        assert!(player.valid_moves().contains(&Action::DoubleDown));
+        let mut shoe = Shoe::stacked(Deck::new());
+        shoe.deal(2);
+        player.get_cards(&mut shoe, 2);
+        assert!(player.valid_moves(&rules).contains(&Action::Hit));
+        assert!(player.valid_moves(&rules).contains(&Action::Stand));
+        assert!(player.valid_moves(&rules).contains(&Action::Surrender));
+        assert!(player.valid_moves(&rules).contains(&Action::DoubleDown));
+        assert!(!player.valid_moves(&rules).contains(&Action::Split));
         player.bet(240);
-        assert!(player.valid_moves().contains(&Action::DoubleDown));
+        assert!(player.valid_moves(&rules).contains(&Action::DoubleDown));
         player.bet(80);
-        assert!(!player.valid_moves().contains(&Action::DoubleDown));
-        player.get_cards(&mut deck, 1);
-        assert!(!player.valid_moves().contains(&Action::Surrender));
+        assert!(!player.valid_moves(&rules).contains(&Action::DoubleDown));
+        player.get_cards(&mut shoe, 1);
+        assert!(!player.valid_moves(&rules).contains(&Action::Surrender));
+    }
+
+    pub fn check_rule_moves() {
+        let mut player = Player::new(500, 0);
+        player.bet(10);
+        player.hand_mut().cards = vec![Card::_test_card(5), Card::_test_card(3)];
+        assert!(player.valid_moves(&TableRules::vegas_strip()).contains(&Action::DoubleDown));
+        assert!(!player.valid_moves(&TableRules::european()).contains(&Action::DoubleDown));
+        assert!(!player.valid_moves(&TableRules::european()).contains(&Action::Surrender));
+        player.hand_mut().cards = vec![Card::_test_card(5), Card::_test_card(5)];
+        player.split();
+        player.hand_mut().cards.push(Card::_test_card(6));
+        assert!(player.valid_moves(&TableRules::vegas_strip()).contains(&Action::DoubleDown));
+        let no_das = TableRules { double_after_split: false, ..TableRules::vegas_strip() };
+        assert!(!player.valid_moves(&no_das).contains(&Action::DoubleDown));
+        assert!(!player.hand().is_soft());
+        player.hand_mut().cards = vec![Card::_test_card(11), Card::_test_card(6)];
+        assert!(player.hand().is_soft());
+        player.hand_mut().cards.push(Card::_test_card(10));
+        assert!(!player.hand().is_soft());
     }
 
     pub fn make_bet() {
         let mut player = Player::new(20, 0);
         let overbet_result = player.bet(30);
-        assert_eq!(overbet_result, Err("Program tried to bet more chips than it has"));
+        assert_eq!(overbet_result, Err(Error::InsufficientChips));
         let mut dealer = Player::new(0, 0);
         let dealer_bet_result = dealer.bet(30);
-        assert_eq!(dealer_bet_result, Err("Program tried to bet as a dealer"));
+        assert_eq!(dealer_bet_result, Err(Error::DealerBet));
         let legal_bet_result = player.bet(10);
         assert_eq!(legal_bet_result, Ok(10));
-        assert_eq!(player.pot, Some(10));
+        assert_eq!(player.pot(), Some(10));
         assert_eq!(player.chips, Some(10));
         assert!(!player.is_broke());
         player.bet(10);
@@ -232,47 +531,202 @@ pub mod tests {
         player.chips = Some(50);
         player.bet(10);
         player.double_down();
-        assert_eq!(player.pot, Some(20));
+        assert_eq!(player.pot(), Some(20));
     }
 
     pub fn check_player_state() {
         let mut player = Player::new(100, 0);
-        let mut deck = Deck::new();
+        let mut shoe = Shoe::stacked(Deck::new());
         assert!(!player.is_in_pot());
         player.bet(20);
         assert!(player.is_in_pot());
         player.resolve_bet(BetResult::Lose);
         assert!(!player.is_in_pot());
-        player.get_cards(&mut deck, 2);
+        player.get_cards(&mut shoe, 2);
         assert!(!player.has_blackjack());
-        deck.deal(9);
+        shoe.deal(9);
         let mut second_player = Player::new(100, 1);
-        second_player.get_cards(&mut deck, 2);
+        second_player.get_cards(&mut shoe, 2);
         assert!(second_player.has_blackjack());
     }
 
     pub fn resolve_bet() {
         let mut player = Player::new(100, 0);
         let no_bet_result = player.resolve_bet(BetResult::StandOff);
-        assert_eq!(no_bet_result, Err("Tried to resolve when no bet was made"));
+        assert_eq!(no_bet_result, Err(Error::NoBet));
         player.bet(30);
         let normal_bet_result = player.resolve_bet(BetResult::StandOff);
         assert_eq!(normal_bet_result, Ok(30));
         assert_eq!(player.chips, Some(100));
-        assert_eq!(player.pot, Some(0));
+        assert_eq!(player.pot(), Some(0));
         player.bet(50);
         player.resolve_bet(BetResult::Win);
         assert_eq!(player.chips, Some(150));
-        assert_eq!(player.pot, Some(0));
+        assert_eq!(player.pot(), Some(0));
         player.bet(100);
         player.resolve_bet(BetResult::Surrender);
         assert_eq!(player.chips, Some(100));
         player.bet(10);
-        player.resolve_bet(BetResult::Blackjack);
+        player.resolve_bet(BetResult::Blackjack(BlackjackPayout::ThreeToTwo));
         assert_eq!(player.chips, Some(115));
         let mut dealer = Player::new(0, 0);
         let dealer_resolve_bet_result = dealer.resolve_bet(BetResult::Lose);
-        assert_eq!(dealer_resolve_bet_result, Err("Tried to resolve bet on a dealer"));
+        assert_eq!(dealer_resolve_bet_result, Err(Error::DealerBet));
+    }
+
+    pub fn split_pair() {
+        let mut player = Player::new(100, 0);
+        player.bet(10);
+        player.hand_mut().cards = vec![Card::_test_card(8), Card::_test_card(8)];
+        assert!(player.valid_moves(&split_rules(4)).contains(&Action::Split));
+        assert!(!player.valid_moves(&split_rules(1)).contains(&Action::Split));
+        assert_eq!(player.split(), Ok(10));
+        assert_eq!(player.chips, Some(80));
+        assert_eq!(player.hands.len(), 2);
+        for hand in player.hands.iter() {
+            assert_eq!(hand.cards.len(), 1);
+            assert_eq!(hand.pot(), 10);
+            assert!(hand.is_split());
+        }
+        player.hand_mut().cards.push(Card::_test_card(3));
+        assert!(!player.valid_moves(&split_rules(4)).contains(&Action::Surrender));
+        assert!(player.valid_moves(&split_rules(4)).contains(&Action::DoubleDown));
+        assert_eq!(player.split(), Err(Error::NotAPair));
+        let mut broke_player = Player::new(10, 0);
+        broke_player.bet(10);
+        broke_player.hand_mut().cards = vec![Card::_test_card(8), Card::_test_card(8)];
+        assert!(!broke_player.valid_moves(&split_rules(4)).contains(&Action::Split));
+        assert_eq!(broke_player.split(), Err(Error::InsufficientChips));
+    }
+
+    pub fn resplit_hands() {
+        let mut player = Player::new(100, 0);
+        player.bet(10);
+        player.hand_mut().cards = vec![Card::_test_card(8), Card::_test_card(8)];
+        player.split();
+        player.hand_mut().cards.push(Card::_test_card(8));
+        assert!(player.valid_moves(&split_rules(3)).contains(&Action::Split));
+        player.split();
+        assert_eq!(player.hands.len(), 3);
+        player.hand_mut().cards.push(Card::_test_card(8));
+        assert!(!player.valid_moves(&split_rules(3)).contains(&Action::Split));
+        assert!(player.valid_moves(&split_rules(4)).contains(&Action::Split));
+        assert_eq!(player.active_hand(), 0);
+        assert!(player.next_hand());
+        assert!(player.next_hand());
+        assert_eq!(player.active_hand(), 2);
+        assert!(!player.next_hand());
+        player.clear_cards();
+        assert_eq!(player.hands.len(), 1);
+        assert_eq!(player.active_hand(), 0);
+    }
+
+    pub fn split_aces() {
+        let mut player = Player::new(100, 0);
+        player.bet(10);
+        player.hand_mut().cards = vec![Card::_test_card(11), Card::_test_card(11)];
+        assert!(!player.hand().is_split_aces());
+        player.split();
+        assert!(player.hand().is_split_aces());
+        player.hand_mut().cards.push(Card::_test_card(10));
+        assert_eq!(player.hand_total(), 21);
+        assert!(!player.has_blackjack());
+    }
+
+    pub fn resolve_split_hands() {
+        let mut player = Player::new(100, 0);
+        player.bet(10);
+        player.hand_mut().cards = vec![Card::_test_card(8), Card::_test_card(8)];
+        player.split();
+        player.next_hand();
+        player.double_down();
+        assert_eq!(player.chips, Some(70));
+        assert_eq!(player.resolve_hand(0, BetResult::Lose), Ok(0));
+        assert!(player.is_in_pot());
+        assert_eq!(player.resolve_hand(1, BetResult::Win), Ok(40));
+        assert!(!player.is_in_pot());
+        assert_eq!(player.chips, Some(110));
+        assert_eq!(player.resolve_hand(1, BetResult::Win), Err(Error::NoBet));
+    }
+
+    pub fn lose_original_bet() {
+        let mut player = Player::new(100, 0);
+        assert_eq!(player.lose_original_bet(), Err(Error::NoBet));
+        player.bet(10);
+        player.hand_mut().cards = vec![Card::_test_card(8), Card::_test_card(8)];
+        player.split();
+        player.double_down();
+        player.next_hand();
+        player.hand_mut().cards.push(Card::_test_card(8));
+        player.split();
+        assert_eq!(player.chips, Some(60));
+        assert_eq!(player.lose_original_bet(), Ok(30));
+        assert_eq!(player.chips, Some(90));
+        assert!(!player.is_in_pot());
+        player.clear_cards();
+        player.bet(10);
+        player.double_down();
+        assert!(player.hand().is_doubled());
+        assert_eq!(player.lose_original_bet(), Ok(10));
+        assert_eq!(player.chips, Some(80));
+        let mut dealer = Player::new(0, 0);
+        assert_eq!(dealer.lose_original_bet(), Err(Error::DealerBet));
+    }
+
+    pub fn insure_bet() {
+        let mut player = Player::new(100, 0);
+        player.bet(40);
+        assert_eq!(player.max_insurance(), 20);
+        assert_eq!(player.insure(30), Err(Error::InsuranceTooLarge));
+        assert_eq!(player.insure(15), Ok(15));
+        assert_eq!(player.insure(10), Err(Error::InsuranceTooLarge));
+        assert_eq!(player.insurance(), 15);
+        assert_eq!(player.pot(), Some(40));
+        assert_eq!(player.chips, Some(45));
+        let mut short_player = Player::new(50, 0);
+        short_player.bet(40);
+        assert_eq!(short_player.max_insurance(), 10);
+        let mut dealer = Player::new(0, 0);
+        assert_eq!(dealer.insure(5), Err(Error::DealerBet));
+    }
+
+    pub fn place_side_bets() {
+        let mut player = Player::new(100, 0);
+        player.bet(20);
+        assert_eq!(player.place_side_bet(SideBetKind::PerfectPairs, 25), Err(Error::SideBetTooLarge));
+        assert_eq!(player.place_side_bet(SideBetKind::PerfectPairs, 15), Ok(15));
+        assert_eq!(player.place_side_bet(SideBetKind::PerfectPairs, 10), Err(Error::SideBetTooLarge));
+        assert_eq!(player.place_side_bet(SideBetKind::PerfectPairs, 5), Ok(5));
+        assert_eq!(player.place_side_bet(SideBetKind::TwentyOnePlusThree, 10), Ok(10));
+        assert_eq!(player.side_bets(), &[(SideBetKind::PerfectPairs, 20), (SideBetKind::TwentyOnePlusThree, 10)]);
+        assert_eq!(player.pot(), Some(20));
+        assert_eq!(player.chips, Some(50));
+        assert_eq!(player.resolve_side_bet(SideBetKind::PerfectPairs, 140), Ok(140));
+        assert_eq!(player.resolve_side_bet(SideBetKind::PerfectPairs, 140), Err(Error::NoBet));
+        assert_eq!(player.resolve_side_bet(SideBetKind::TwentyOnePlusThree, 0), Ok(0));
+        assert!(player.side_bets().is_empty());
+        assert_eq!(player.chips, Some(190));
+        let mut dealer = Player::new(0, 0);
+        assert_eq!(dealer.place_side_bet(SideBetKind::PerfectPairs, 5), Err(Error::DealerBet));
+    }
+
+    pub fn resolve_insurance() {
+        let mut player = Player::new(100, 0);
+        assert_eq!(player.resolve_insurance(true), Err(Error::NoInsurance));
+        player.bet(20);
+        player.insure(10);
+        assert_eq!(player.resolve_insurance(true), Ok(30));
+        assert_eq!(player.insurance(), 0);
+        assert_eq!(player.resolve_bet(BetResult::Lose), Ok(0));
+        assert_eq!(player.chips, Some(100));
+        player.bet(20);
+        player.insure(10);
+        assert_eq!(player.resolve_insurance(false), Ok(0));
+        assert_eq!(player.resolve_bet(BetResult::Win), Ok(40));
+        assert_eq!(player.chips, Some(110));
+        player.bet(10);
+        assert_eq!(player.resolve_bet(BetResult::EvenMoney), Ok(20));
+        assert_eq!(player.chips, Some(120));
     }
 
     pub fn create_player_list() {
@@ -285,15 +739,15 @@ pub mod tests {
     }
 
     pub fn clear_player_cards() {
-        let mut deck = Deck::new();
+        let mut shoe = Shoe::stacked(Deck::new());
         let mut player_list = PlayerList::new(5, 100);
         for player in player_list.iter_mut() {
-            player.get_cards(&mut deck, 2);
-            assert_eq!(player.hand.len(), 2);
+            player.get_cards(&mut shoe, 2);
+            assert_eq!(player.hand().cards.len(), 2);
         }
-        player_list.clear_cards();
+        assert_eq!(player_list.clear_cards().len(), 10);
         for player in player_list.iter_mut() {
-            assert_eq!(player.hand.len(), 0);
+            assert_eq!(player.hand().cards.len(), 0);
         }
     }
 }
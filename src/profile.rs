@@ -0,0 +1,355 @@
+use crate::error::{ Error, Result };
+use crate::player::{ Action, BetResult };
+use crate::progress::{ format_timestamp, load_or_default, save_toml, timestamp };
+use crate::table::{ Event, Table };
+use serde::{ Deserialize, Serialize };
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A profile's results over every session it has played.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct LifetimeStats {
+    /// Hands dealt, counting each split hand.
+    pub hands: u32,
+    pub wins: u32,
+    pub blackjacks: u32,
+    pub busts: u32,
+    /// The most chips won in a single round.
+    pub biggest_win: u32,
+    /// Chips won less chips lost.
+    pub net: i64,
+}
+
+impl LifetimeStats {
+    /// Adds one round's `events` for `seat`, which won `net` chips over the round.
+    pub fn record_round(&mut self, seat: usize, events: &[Event], net: i64) {
+        let mut hands = BTreeSet::new();
+        for event in events.iter().filter(|e| e.seat() == Some(seat)) {
+            match event {
+                Event::PlayerCard { hand, .. } => {
+                    hands.insert(*hand);
+                },
+                Event::HandSettled { result: BetResult::Win, .. } => self.wins += 1,
+                Event::HandSettled { result: BetResult::Blackjack(_), .. } | Event::EvenMoneyPaid { .. } => {
+                    self.wins += 1;
+                    self.blackjacks += 1;
+                },
+                Event::HandBusted { .. } => self.busts += 1,
+                _ => (),
+            }
+        }
+        self.hands += hands.len() as u32;
+        self.biggest_win = self.biggest_win.max(net.max(0) as u32);
+        self.net += net;
+    }
+
+    /// The share of hands won, from 0 to 1.
+    pub fn win_rate(&self) -> f64 {
+        match self.hands {
+            0 => 0.0,
+            n => self.wins as f64 / n as f64,
+        }
+    }
+}
+
+impl fmt::Display for LifetimeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hands, {} won ({:.0}%), {} blackjacks, {} busts, biggest win {}, net {:+}",
+            self.hands,
+            self.wins,
+            100.0 * self.win_rate(),
+            self.blackjacks,
+            self.busts,
+            self.biggest_win,
+            self.net,
+        )
+    }
+}
+
+/// One game a profile played, from its first round to its last.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Session {
+    /// When the session started and when its last round ended, in seconds since the
+    /// Unix epoch.
+    pub started: u64,
+    pub ended: u64,
+    /// The name of the table rules played.
+    pub rules: String,
+    /// Rounds the profile bet on.
+    pub rounds: u32,
+    /// Chips bet, counting doubles, splits, insurance and side bets.
+    pub wagered: u64,
+    pub net: i64,
+    /// The most chips the profile had at the start or after any round.
+    pub peak_bankroll: u32,
+    /// The most rounds won in a row. A push neither adds to a run nor ends it.
+    pub longest_streak: u32,
+    /// The run of won rounds the session is on.
+    pub streak: u32,
+}
+
+impl Session {
+    pub fn new(rules: &str, bankroll: u32) -> Session {
+        let now = timestamp();
+        Session {
+            started: now,
+            ended: now,
+            rules: String::from(rules),
+            rounds: 0,
+            wagered: 0,
+            net: 0,
+            peak_bankroll: bankroll,
+            longest_streak: 0,
+            streak: 0,
+        }
+    }
+
+    /// Adds one round's `events` for `seat`, which won `net` chips and was left with
+    /// `bankroll`. Rounds the seat sat out are skipped.
+    pub fn record_round(&mut self, seat: usize, events: &[Event], net: i64, bankroll: u32) {
+        let mut bet = 0;
+        let mut wagered = 0;
+        for event in events.iter().filter(|e| e.seat() == Some(seat)) {
+            match *event {
+                Event::BetPlaced { amount, .. } => {
+                    bet = amount;
+                    wagered += amount as u64;
+                },
+                Event::ActionTaken { action: Action::DoubleDown | Action::Split, .. } => wagered += bet as u64,
+                Event::InsuranceTaken { amount, .. } | Event::SideBetPlaced { amount, .. } => wagered += amount as u64,
+                _ => (),
+            }
+        }
+        if wagered == 0 {
+            return;
+        }
+        self.ended = timestamp();
+        self.rounds += 1;
+        self.wagered += wagered;
+        self.net += net;
+        self.peak_bankroll = self.peak_bankroll.max(bankroll);
+        match net {
+            n if n > 0 => self.streak += 1,
+            n if n < 0 => self.streak = 0,
+            _ => (),
+        }
+        self.longest_streak = self.longest_streak.max(self.streak);
+    }
+
+    /// Net winnings as a share of the chips wagered.
+    pub fn roi(&self) -> f64 {
+        match self.wagered {
+            0 => 0.0,
+            wagered => self.net as f64 / wagered as f64,
+        }
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}: {} rounds, wagered {}, net {:+} (ROI {:+.1}%), peak bankroll {}, longest win streak {}",
+            format_timestamp(self.started),
+            self.rules,
+            self.rounds,
+            self.wagered,
+            self.net,
+            100.0 * self.roi(),
+            self.peak_bankroll,
+            self.longest_streak,
+        )
+    }
+}
+
+/// A named player whose bankroll and stats carry over from one session to the next.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Profile {
+    pub name: String,
+    /// When the profile was made, in seconds since the Unix epoch.
+    pub created: u64,
+    pub bankroll: u32,
+    #[serde(default)]
+    pub stats: LifetimeStats,
+    /// Every game played, oldest first.
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} chips)", self.name, self.bankroll)
+    }
+}
+
+/// Every profile on this machine, saved between sessions.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Profiles {
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub const PATH: &'static str = "blackjack_profiles.toml";
+    /// The longest name a profile can have.
+    pub const MAX_NAME: usize = 20;
+
+    pub fn load(path: &str) -> Result<Profiles> {
+        load_or_default(path)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        save_toml(self, path)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    /// Adds a profile named `name` with `bankroll` chips. Names are trimmed and must be
+    /// new, ignoring case.
+    pub fn create(&mut self, name: &str, bankroll: u32) -> Result<&Profile> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > Profiles::MAX_NAME {
+            return Err(Error::InvalidProfile(format!("a name needs 1 to {} characters", Profiles::MAX_NAME)));
+        }
+        if self.profiles.iter().any(|p| p.name.to_lowercase() == name.to_lowercase()) {
+            return Err(Error::InvalidProfile(format!("{} is already taken", name)));
+        }
+        self.profiles.push(Profile {
+            name: String::from(name),
+            created: timestamp(),
+            bankroll,
+            stats: LifetimeStats::default(),
+            sessions: Vec::new(),
+        });
+
+        Ok(self.profiles.last().unwrap())
+    }
+
+    /// The profile in each named seat at `table`, by seat.
+    fn seated<'a>(&'a mut self, table: &'a Table) -> impl Iterator<Item = (usize, &'a mut Profile)> + 'a {
+        self.profiles.iter_mut().filter_map(|profile| {
+            let seat = table.players().iter().position(|p| p.name.as_ref() == Some(&profile.name))?;
+            Some((seat, profile))
+        })
+    }
+
+    /// Starts a new session for the profile in every named seat at `table`. Call it
+    /// before the first round of a game.
+    pub fn start_sessions(&mut self, table: &Table) {
+        for (seat, profile) in self.seated(table) {
+            let chips = table.players()[seat].chips.unwrap_or(0);
+            profile.sessions.push(Session::new(&table.rules().name, chips));
+        }
+    }
+
+    /// Updates the profile in every named seat at `table` after a round: its bankroll
+    /// becomes the seat's chips and the round's `events` go into its stats and latest
+    /// session. `chips_before` holds each seat's chips before the round.
+    pub fn record_round(&mut self, table: &Table, chips_before: &[u32], events: &[Event]) {
+        for (seat, profile) in self.seated(table) {
+            let chips = table.players()[seat].chips.unwrap_or(0);
+            let net = chips as i64 - chips_before[seat] as i64;
+            profile.stats.record_round(seat, events, net);
+            if let Some(session) = profile.sessions.last_mut() {
+                session.record_round(seat, events, net, chips);
+            }
+            profile.bankroll = chips;
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::input::{ self, BasicStrategy, Move, PlayerInput, Scripted };
+    use crate::player::{ Action, PlayerList };
+    use crate::rules::TableRules;
+    use crate::table::Phase;
+    use crate::table::tests::stacked_shoe;
+
+    pub fn create_profiles() {
+        let mut profiles = Profiles::default();
+        assert_eq!(profiles.create("  Ada ", 500).unwrap().name, "Ada");
+        assert_eq!(profiles.create("ada", 500), Err(Error::InvalidProfile(String::from("ada is already taken"))));
+        assert!(profiles.create("   ", 500).is_err());
+        assert!(profiles.create("A name far too long to fit", 500).is_err());
+        profiles.create("Grace", 300).unwrap();
+        assert_eq!(format!("{}", profiles.get("Grace").unwrap()), "Grace (300 chips)");
+        assert!(profiles.get("Alan").is_none());
+
+        let path = std::env::temp_dir().join(format!("blackjack_profiles_{}.toml", std::process::id()));
+        let path = path.to_str().unwrap();
+        profiles.save(path).unwrap();
+        assert_eq!(Profiles::load(path), Ok(profiles));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    pub fn record_profile_rounds() {
+        // Ada splits 8s against a 10, hitting 8, 4 into a bust and making 19 with an Ace on
+        // the second hand; Grace has a blackjack and seat 3 pushes 17s with the dealer.
+        let shoe = stacked_shoe(&[8, 11, 10, 10, 8, 10, 7, 7, 4, 10, 11]);
+        let mut players = PlayerList::from_chips(&[500, 300, 100]);
+        players[0].name = Some(String::from("Ada"));
+        players[1].name = Some(String::from("Grace"));
+        let mut table = Table::with_shoe(TableRules::vegas_strip(), players, 10, shoe);
+        let mut seats: Vec<Box<dyn PlayerInput>> = vec![
+            Box::new(Scripted::new(vec![
+                Move::Bet(20),
+                Move::Action(Action::Split),
+                Move::Action(Action::Hit),
+                Move::Action(Action::Stand),
+            ])),
+            Box::new(BasicStrategy::new(2)),
+            Box::new(BasicStrategy::default()),
+        ];
+        let mut profiles = Profiles::default();
+        profiles.create("Ada", 500).unwrap();
+        profiles.create("Grace", 300).unwrap();
+        profiles.start_sessions(&table);
+        let chips_before: Vec<u32> = table.players().iter().map(|p| p.chips.unwrap()).collect();
+        let mut events = table.start_round().unwrap();
+        while table.phase() != Phase::RoundOver {
+            events.extend(input::step(&mut table, &mut seats).unwrap());
+        }
+        profiles.record_round(&table, &chips_before, &events);
+
+        let ada = profiles.get("Ada").unwrap();
+        assert_eq!(ada.bankroll, 500);
+        assert_eq!(ada.stats, LifetimeStats { hands: 2, wins: 1, blackjacks: 0, busts: 1, biggest_win: 0, net: 0 });
+        let grace = profiles.get("Grace").unwrap();
+        assert_eq!(grace.bankroll, 330);
+        assert_eq!(grace.stats, LifetimeStats { hands: 1, wins: 1, blackjacks: 1, busts: 0, biggest_win: 30, net: 30 });
+        assert_eq!(format!("{}", grace.stats), "1 hands, 1 won (100%), 1 blackjacks, 0 busts, biggest win 30, net +30");
+
+        let session = &ada.sessions[0];
+        assert_eq!((session.rounds, session.wagered, session.net, session.peak_bankroll), (1, 40, 0, 500));
+        assert_eq!((session.streak, session.longest_streak), (0, 0));
+        let session = &grace.sessions[0];
+        assert_eq!((session.wagered, session.peak_bankroll, session.streak), (20, 330, 1));
+        assert_eq!(session.rules, "Vegas Strip");
+        assert_eq!(session.roi(), 1.5);
+        assert!(format!("{}", session).ends_with(
+            "Vegas Strip: 1 rounds, wagered 20, net +30 (ROI +150.0%), peak bankroll 330, longest win streak 1"
+        ));
+
+        // A round sat out leaves the session alone, and a loss ends the run of wins.
+        let mut session = Session::new("Vegas Strip", 100);
+        session.record_round(0, &[], 0, 100);
+        assert_eq!(session.rounds, 0);
+        for (net, bankroll) in [(10, 110), (0, 110), (10, 120), (-20, 100), (10, 110)] {
+            session.record_round(0, &[Event::BetPlaced { seat: 0, amount: 10 }], net, bankroll);
+        }
+        assert_eq!((session.rounds, session.wagered, session.net), (5, 50, 10));
+        assert_eq!((session.peak_bankroll, session.longest_streak, session.streak), (120, 2, 1));
+    }
+}
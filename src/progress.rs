@@ -0,0 +1,224 @@
+use crate::count::CountSystem;
+use crate::error::{ Error, Result };
+use serde::{ Deserialize, Serialize };
+use std::{ fmt, fs, io, time };
+
+/// Seconds since the Unix epoch, used to date saved records.
+pub fn timestamp() -> u64 {
+    time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time, such as
+/// "2024-02-29 13:05:09 UTC".
+pub fn format_timestamp(secs: u64) -> String {
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`.
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    let time = secs % 86_400;
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, time / 3_600, time % 3_600 / 60, time % 60)
+}
+
+/// Reads a UTC date such as "2024-02-29" as the seconds since the Unix epoch at its
+/// midnight, or `None` if it is not a real date on or after 1970-01-01.
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.trim().splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = [31, 28 + leap as i64, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if year < 1970 || !(1..=12).contains(&month) || day < 1 || day > month_days[month as usize - 1] {
+        return None;
+    }
+    // A civil date to days, after Howard Hinnant's `days_from_civil`.
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    Some((era * 146_097 + day_of_era - 719_468) as u64 * 86_400)
+}
+
+/// Reads a TOML file into `T`, or `T::default()` when the file does not exist yet.
+pub(crate) fn load_or_default<T>(path: &str) -> Result<T>
+    where T: Default + for<'de> Deserialize<'de>
+{
+    match fs::read_to_string(path) {
+        Ok(source) => toml::from_str(&source).map_err(|e| Error::Parse(e.to_string())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(Error::Io(format!("Could not read {}: {}", path, e))),
+    }
+}
+
+pub(crate) fn save_toml<T: Serialize>(value: &T, path: &str) -> Result<()> {
+    let source = toml::to_string(value).map_err(|e| Error::Parse(e.to_string()))?;
+    fs::write(path, source).map_err(|e| Error::Io(format!("Could not write {}: {}", path, e)))
+}
+
+/// One finished counting drill.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DrillRecord {
+    pub timestamp: u64,
+    pub system: CountSystem,
+    /// Cards shown during the drill.
+    pub cards: usize,
+    /// Time spent dealing, leaving out the time spent answering.
+    pub seconds: f64,
+    /// Running count questions asked and answered correctly.
+    pub asked: u32,
+    pub correct: u32,
+    /// The target time for a full deck, if one was set.
+    pub goal: Option<f64>,
+}
+
+impl DrillRecord {
+    /// The time the drill's pace works out to for a full 52-card deck.
+    pub fn deck_seconds(&self) -> f64 {
+        self.seconds * 52.0 / self.cards as f64
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        match self.asked {
+            0 => 0.0,
+            n => 100.0 * self.correct as f64 / n as f64,
+        }
+    }
+
+    /// Whether the deck pace beat the goal with every count right.
+    pub fn met_goal(&self) -> Option<bool> {
+        self.goal.map(|goal| self.deck_seconds() <= goal && self.correct == self.asked)
+    }
+}
+
+impl fmt::Display for DrillRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} cards in {:.1}s ({:.1}s a deck), {}/{} counts right",
+            self.system,
+            self.cards,
+            self.seconds,
+            self.deck_seconds(),
+            self.correct,
+            self.asked,
+        )?;
+        match self.met_goal() {
+            Some(true) => write!(f, ", goal of {:.0}s met", self.goal.unwrap()),
+            Some(false) => write!(f, ", goal of {:.0}s missed", self.goal.unwrap()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Every counting drill played, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct DrillHistory {
+    #[serde(default)]
+    pub drills: Vec<DrillRecord>,
+}
+
+impl DrillHistory {
+    pub const PATH: &'static str = "blackjack_drills.toml";
+
+    /// Loads the history at `path`, starting an empty one if there is no file yet.
+    pub fn load(path: &str) -> Result<DrillHistory> {
+        load_or_default(path)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        save_toml(self, path)
+    }
+
+    pub fn record(&mut self, drill: DrillRecord) {
+        self.drills.push(drill);
+    }
+
+    /// The fastest deck pace among drills with every count right.
+    pub fn best_deck_seconds(&self) -> Option<f64> {
+        self.drills.iter()
+            .filter(|d| d.asked > 0 && d.correct == d.asked)
+            .map(|d| d.deck_seconds())
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Up to `n` of the latest drills, newest last.
+    pub fn recent(&self, n: usize) -> &[DrillRecord] {
+        &self.drills[self.drills.len().saturating_sub(n)..]
+    }
+
+    /// The average deck pace and accuracy over the latest `n` drills, to show progress.
+    pub fn trend(&self, n: usize) -> Option<(f64, f64)> {
+        let recent = self.recent(n);
+        if recent.is_empty() {
+            return None;
+        }
+        let pace = recent.iter().map(|d| d.deck_seconds()).sum::<f64>() / recent.len() as f64;
+        let accuracy = recent.iter().map(|d| d.accuracy()).sum::<f64>() / recent.len() as f64;
+
+        Some((pace, accuracy))
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use std::env;
+
+    fn drill(cards: usize, seconds: f64, asked: u32, correct: u32, goal: Option<f64>) -> DrillRecord {
+        DrillRecord { timestamp: 0, system: CountSystem::HiLo, cards, seconds, asked, correct, goal }
+    }
+
+    pub fn format_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_709_211_909), "2024-02-29 13:05:09 UTC");
+        assert_eq!(format_timestamp(4_102_444_799), "2099-12-31 23:59:59 UTC");
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-02-29"), Some(951_782_400));
+        assert_eq!(parse_date(" 2024-02-29"), Some(1_709_164_800));
+        assert_eq!(parse_date("2099-12-31").map(format_timestamp).unwrap(), "2099-12-31 00:00:00 UTC");
+        for date in ["2023-02-29", "2024-13-01", "2024-04-31", "1969-12-31", "2024-02", "yesterday"] {
+            assert_eq!(parse_date(date), None);
+        }
+    }
+
+    pub fn drill_records() {
+        let fast = drill(26, 10.0, 2, 2, Some(25.0));
+        assert_eq!(fast.deck_seconds(), 20.0);
+        assert_eq!(fast.accuracy(), 100.0);
+        assert_eq!(fast.met_goal(), Some(true));
+        assert_eq!(format!("{}", fast), "Hi-Lo: 26 cards in 10.0s (20.0s a deck), 2/2 counts right, goal of 25s met");
+        let sloppy = drill(51, 20.0, 4, 3, Some(25.0));
+        assert_eq!(sloppy.met_goal(), Some(false));
+        assert_eq!(drill(52, 30.0, 1, 1, None).met_goal(), None);
+
+        let mut history = DrillHistory::default();
+        assert_eq!(history.best_deck_seconds(), None);
+        assert_eq!(history.trend(5), None);
+        history.record(sloppy);
+        history.record(fast);
+        history.record(drill(52, 30.0, 1, 1, None));
+        assert_eq!(history.best_deck_seconds(), Some(20.0));
+        assert_eq!(history.recent(2).len(), 2);
+        assert_eq!(history.trend(2), Some((25.0, 100.0)));
+
+        let path = env::temp_dir().join(format!("blackjack_drills_{}.toml", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(DrillHistory::load(path), Ok(DrillHistory::default()));
+        history.save(path).unwrap();
+        assert_eq!(DrillHistory::load(path), Ok(history));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+use crate::describe;
+use crate::io::get_user_str;
+use blackjack::history::event_line;
+use blackjack::{ Action, Event, HandHistory, Table };
+
+const PROMPT: &str = "Enter: next card, b: back a card, r N: jump to round N, q: quit";
+
+pub struct ReplayOptions {
+    pub path: String,
+    pub round: Option<u32>,
+    /// The seat to follow, from zero; the dealer is always shown.
+    pub seat: Option<usize>,
+    pub verify: bool,
+}
+
+/// Reads the `replay` arguments: the history file followed by any flags.
+pub fn parse_options(args: &[String]) -> Result<ReplayOptions, String> {
+    let (path, flags) = args.split_first().ok_or("replay needs a hand history file")?;
+    let mut options = ReplayOptions { path: path.clone(), round: None, seat: None, verify: false };
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        if flag == "--verify" {
+            options.verify = true;
+            continue;
+        }
+        let value = flags.next().ok_or(format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--round" => options.round = Some(value.parse().map_err(|_| format!("'{}' is not a round", value))?),
+            "--seat" => {
+                let seat = value.parse::<usize>().ok().filter(|&n| n > 0);
+                options.seat = Some(seat.ok_or(format!("'{}' is not a seat", value))? - 1);
+            },
+            _ => return Err(format!("Unknown option {}", flag)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// One line of a replay: an event, the round it belongs to, and the table as it stood
+/// once the event's batch was played, or `None` if the round did not replay.
+struct Frame {
+    index: usize,
+    event: Event,
+    table: Option<Table>,
+    last_action: Option<Action>,
+}
+
+impl Frame {
+    fn is_card(&self) -> bool {
+        matches!(
+            self.event,
+            Event::PlayerCard { .. }
+                | Event::DealerUpcard { .. }
+                | Event::HoleCardRevealed { .. }
+                | Event::DealerCard { .. }
+                | Event::RoundEnded
+        )
+    }
+}
+
+/// Replays every round through the table, falling back to the recorded lines for any
+/// round that plays out differently, and keeps the events `seat` should see.
+fn frames(history: &HandHistory, seat: Option<usize>) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    for (index, record) in history.rounds.iter().enumerate() {
+        let mut round = Vec::new();
+        let mut last_action = None;
+        let replayed = history.replay(index, |table, events| {
+            for event in events {
+                round.push(Frame { index, event: event.clone(), table: Some(table.clone()), last_action: last_action.clone() });
+                describe(table, event, &mut last_action);
+            }
+        });
+        if let Err(e) = replayed {
+            println!("{}, showing it as recorded", e);
+            round = record.events.iter()
+                .map(|event| Frame { index, event: event.clone(), table: None, last_action: None })
+                .collect();
+        }
+        frames.extend(round.into_iter().filter(|f| seat.is_none() || f.event.seat().is_none() || f.event.seat() == seat));
+    }
+
+    frames
+}
+
+/// Groups frames into steps that each end on a dealt card or the end of a round.
+fn steps(frames: Vec<Frame>) -> Vec<Vec<Frame>> {
+    let mut steps = vec![Vec::new()];
+    for frame in frames {
+        let is_card = frame.is_card();
+        steps.last_mut().unwrap().push(frame);
+        if is_card {
+            steps.push(Vec::new());
+        }
+    }
+    steps.retain(|step| !step.is_empty());
+
+    steps
+}
+
+fn show(history: &HandHistory, seat: Option<usize>, frame: &Frame) {
+    let message = frame.table.as_ref()
+        .and_then(|table| describe(table, &frame.event, &mut frame.last_action.clone()))
+        .or_else(|| event_line(&frame.event));
+    match (message, &frame.event) {
+        (Some(message), _) => println!("{}", message),
+        (None, Event::RoundStarted { round }) => println!("\nRound {}", round),
+        (None, _) => {
+            for (n, chips) in history.rounds[frame.index].chips.iter().enumerate() {
+                if seat.is_none() || seat == Some(n) {
+                    println!("Player {} has {} chips", n + 1, chips);
+                }
+            }
+        },
+    }
+}
+
+fn verify(history: &HandHistory) {
+    let mut matching = 0;
+    for index in 0..history.rounds.len() {
+        match history.replay(index, |_, _| ()) {
+            Ok(()) => matching += 1,
+            Err(e) => println!("{}", e),
+        }
+    }
+    println!("{} of {} rounds follow the rules", matching, history.rounds.len());
+}
+
+/// Asks where to go from `position` until the answer is somewhere to go; `None` quits.
+fn next_position<F: Fn(u32) -> Option<usize>>(position: usize, last: usize, round_start: F) -> Option<usize> {
+    loop {
+        let command = get_user_str(None);
+        if command.is_empty() {
+            return None;
+        }
+        match command.trim().to_lowercase().as_str() {
+            "" | "n" if position == last => println!("That was the last card, b to go back or q to quit"),
+            "" | "n" => return Some(position + 1),
+            "b" if position == 0 => println!("That was the first card, press enter to go on or q to quit"),
+            "b" => return Some(position - 1),
+            "q" => return None,
+            command => match command.strip_prefix('r').and_then(|n| n.trim().parse::<u32>().ok()) {
+                Some(round) => match round_start(round) {
+                    Some(start) => return Some(start),
+                    None => println!("There is no round {}", round),
+                },
+                None => println!("{}", PROMPT),
+            },
+        }
+    }
+}
+
+/// Steps through a hand history a card at a time, in the words the game used.
+pub fn replay(options: ReplayOptions) {
+    let history = match HandHistory::load(&options.path) {
+        Ok(history) => history,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        },
+    };
+    let header = &history.header;
+    println!("Session {} with {} player(s), seed {}", header.session, header.players, header.seed);
+    println!("{}", header.rules);
+    if options.verify {
+        verify(&history);
+        return;
+    }
+    let steps = steps(frames(&history, options.seat));
+    if steps.is_empty() {
+        println!("The hand history has no rounds to replay");
+        return;
+    }
+    let round_start = |round: u32| steps.iter().position(|step| history.rounds[step[0].index].round == round);
+    let mut position = match options.round.map(|round| (round, round_start(round))) {
+        Some((_, Some(start))) => start,
+        Some((round, None)) => {
+            println!("There is no round {}, starting from the first", round);
+            0
+        },
+        None => 0,
+    };
+    println!("{}", PROMPT);
+    loop {
+        for frame in steps[position].iter() {
+            show(&history, options.seat, frame);
+        }
+        match next_position(position, steps.len() - 1, round_start) {
+            Some(next) => position = next,
+            None => break,
+        }
+    }
+}
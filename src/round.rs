@@ -0,0 +1,120 @@
+use crate::deck::Card;
+use crate::hand::Hand;
+use crate::player::BetResult;
+use crate::error::Result;
+use crate::rules::TableRules;
+use crate::shoe::Shoe;
+
+/// Draws to the dealer's hand until the table's soft 17 rule says stand, returning
+/// the cards drawn in order. `on_card` sees the hand after each card, and whether the
+/// shoe ran dry and took its discards back to deal it.
+pub fn play_dealer<F>(dealer: &mut Hand, shoe: &mut Shoe, rules: &TableRules, mut on_card: F) -> Result<Vec<Card>>
+    where F: FnMut(&Hand, bool)
+{
+    let mut drawn = Vec::new();
+    while rules.dealer_hits(dealer) {
+        let card = shoe.deal(1)?;
+        drawn.extend(card.iter().cloned());
+        dealer.cards.extend(card);
+        on_card(dealer, shoe.take_refill());
+    }
+
+    Ok(drawn)
+}
+
+/// How a finished, unbusted player hand fares against the dealer's final hand.
+pub fn hand_result(hand: &Hand, dealer: &Hand, rules: &TableRules) -> BetResult {
+    let dealer_total = dealer.total();
+    match hand.total() {
+        _ if hand.is_busted() => BetResult::Lose,
+        _ if hand.is_natural() && dealer.is_natural() => BetResult::StandOff,
+        _ if hand.is_natural() => BetResult::Blackjack(rules.blackjack_payout),
+        _ if dealer.is_natural() => BetResult::Lose,
+        _ if rules.is_charlie(hand) => BetResult::Win,
+        n if dealer.is_busted() || n > dealer_total => BetResult::Win,
+        n if n == dealer_total => BetResult::StandOff,
+        _ => BetResult::Lose,
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::deck::Deck;
+    use crate::error::Error;
+    use crate::rules::BlackjackPayout;
+    use crate::table::tests::stacked_shoe;
+
+    /// Plays out a dealer starting on `start` and drawing from `values`, checking the
+    /// callback sees the hand after every card.
+    fn dealer_draws(start: Vec<u8>, values: &[u8], rules: &TableRules) -> (Vec<u8>, u8) {
+        let mut dealer = Hand::_test_hand(start);
+        let mut seen = Vec::new();
+        let drawn = play_dealer(&mut dealer, &mut stacked_shoe(values), rules, |hand, refilled| {
+            assert!(!refilled);
+            seen.push(hand.cards.clone());
+        }).unwrap();
+        let hands: Vec<Vec<Card>> = (1..=drawn.len()).map(|n| dealer.cards[..dealer.cards.len() - drawn.len() + n].to_vec()).collect();
+        assert_eq!(seen, hands);
+
+        (drawn.iter().map(|c| c.numeric_value()).collect(), dealer.total())
+    }
+
+    pub fn settle_hand_results() {
+        let rules = TableRules::vegas_strip();
+        let natural = Hand::_test_hand(vec![11, 10]);
+        let twenty = Hand::_test_hand(vec![10, 10]);
+        let three_card_21 = Hand::_test_hand(vec![10, 5, 6]);
+        let dealer_bust = Hand::_test_hand(vec![10, 6, 10]);
+        assert!(matches!(hand_result(&natural, &twenty, &rules), BetResult::Blackjack(BlackjackPayout::ThreeToTwo)));
+        assert!(matches!(hand_result(&natural, &natural, &rules), BetResult::StandOff));
+        assert!(matches!(hand_result(&three_card_21, &natural, &rules), BetResult::Lose));
+        assert!(matches!(hand_result(&three_card_21, &twenty, &rules), BetResult::Win));
+        assert!(matches!(hand_result(&twenty, &twenty, &rules), BetResult::StandOff));
+        assert!(matches!(hand_result(&twenty, &three_card_21, &rules), BetResult::Lose));
+        assert!(matches!(hand_result(&twenty, &dealer_bust, &rules), BetResult::Win));
+        assert!(matches!(hand_result(&dealer_bust, &twenty, &rules), BetResult::Lose));
+        let charlie = Hand::_test_hand(vec![2, 3, 2, 4, 2]);
+        assert!(matches!(hand_result(&charlie, &twenty, &rules), BetResult::Lose));
+        let charlie_rules = TableRules { charlie: Some(5), ..TableRules::vegas_strip() };
+        assert!(matches!(hand_result(&charlie, &twenty, &charlie_rules), BetResult::Win));
+    }
+
+    pub fn soft_17_sequences() {
+        let s17 = TableRules::vegas_strip();
+        let h17 = TableRules::downtown_vegas();
+        // Ace, 6 stands on S17 and draws on H17.
+        assert_eq!(dealer_draws(vec![11, 6], &[10], &s17), (vec![], 17));
+        assert_eq!(dealer_draws(vec![11, 6], &[10], &h17), (vec![10], 17));
+        assert_eq!(dealer_draws(vec![11, 6], &[4], &h17), (vec![4], 21));
+        // Soft 17 built from several cards: Ace, 2 then a 4.
+        assert_eq!(dealer_draws(vec![11, 2], &[4, 3], &s17), (vec![4], 17));
+        assert_eq!(dealer_draws(vec![11, 2], &[4, 3], &h17), (vec![4, 3], 20));
+        // Two aces: Ace, Ace, 5 is soft 17, and the next Ace makes soft 18.
+        assert_eq!(dealer_draws(vec![11, 11], &[5, 11], &s17), (vec![5], 17));
+        assert_eq!(dealer_draws(vec![11, 11], &[5, 11], &h17), (vec![5, 11], 18));
+        // 6, Ace is soft 17 and hitting to a hard 12 keeps going.
+        assert_eq!(dealer_draws(vec![6, 11], &[5, 10, 2], &h17), (vec![5, 10], 22));
+        assert_eq!(dealer_draws(vec![6, 11], &[5, 10, 2], &s17), (vec![], 17));
+        // Hard 17 stands either way.
+        assert_eq!(dealer_draws(vec![10, 7], &[4], &h17), (vec![], 17));
+        assert_eq!(dealer_draws(vec![10, 6, 11], &[4], &h17), (vec![], 17));
+    }
+
+    pub fn dealer_draws_from_discards() {
+        let mut deck = Deck::new();
+        deck.deal(52);
+        let mut shoe = Shoe::stacked(deck);
+        shoe.discard(vec![Card::_test_card(5)]);
+        let mut dealer = Hand::_test_hand(vec![10, 6]);
+        let mut refills = Vec::new();
+        play_dealer(&mut dealer, &mut shoe, &TableRules::vegas_strip(), |_, refilled| refills.push(refilled)).unwrap();
+        assert_eq!(refills, vec![true]);
+        assert_eq!(dealer.total(), 21);
+        // With nothing left to deal, the dealer can't finish.
+        let mut dealer = Hand::_test_hand(vec![10, 6]);
+        assert_eq!(play_dealer(&mut dealer, &mut shoe, &TableRules::vegas_strip(), |_, _| ()), Err(Error::ShoeEmpty));
+    }
+}
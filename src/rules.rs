@@ -0,0 +1,389 @@
+use crate::deck::Card;
+use crate::error::{ Error, Result };
+use crate::hand::Hand;
+use crate::shoe::Shoe;
+use crate::side_bet::{ SideBet, SideBetKind };
+use serde::{ Deserialize, Serialize };
+use std::{ fmt, fs };
+
+/// When the dealer's second card is dealt and checked for blackjack.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HoleCardRule {
+    /// The dealer checks the hole card for blackjack when showing an Ace or ten-value card.
+    Peek,
+    /// The dealer takes a hole card but leaves it face down until the players have acted.
+    NoPeek,
+    /// The dealer's second card is only dealt once the players have acted, and players
+    /// lose only their original bets to a dealer blackjack.
+    European,
+}
+
+impl HoleCardRule {
+    pub const ALL: [HoleCardRule; 3] = [HoleCardRule::Peek, HoleCardRule::NoPeek, HoleCardRule::European];
+
+    pub fn dealer_peeks(&self, upcard: &Card) -> bool {
+        *self == HoleCardRule::Peek && upcard.numeric_value() >= 10
+    }
+
+    pub fn deals_hole_card(&self) -> bool {
+        *self != HoleCardRule::European
+    }
+}
+
+/// What a player's natural pays.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum BlackjackPayout {
+    #[serde(rename = "3:2")]
+    ThreeToTwo,
+    #[serde(rename = "6:5")]
+    SixToFive,
+    #[serde(rename = "1:1")]
+    EvenMoney,
+}
+
+impl BlackjackPayout {
+    /// Chips returned for a winning natural, including the original stake.
+    pub fn pay(&self, pot: u32) -> u32 {
+        match self {
+            BlackjackPayout::ThreeToTwo => pot + pot * 3 / 2,
+            BlackjackPayout::SixToFive => pot + pot * 6 / 5,
+            BlackjackPayout::EvenMoney => pot * 2,
+        }
+    }
+}
+
+/// Which two-card totals may be doubled.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoubleRule {
+    AnyTwo,
+    NineToEleven,
+    TenToEleven,
+}
+
+impl DoubleRule {
+    pub fn allows(&self, total: u8) -> bool {
+        match self {
+            DoubleRule::AnyTwo => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&total),
+            DoubleRule::TenToEleven => (10..=11).contains(&total),
+        }
+    }
+}
+
+/// Whether, and when, a player may give up half their bet.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SurrenderRule {
+    NotAllowed,
+    /// Surrender is offered after the dealer has checked for blackjack.
+    Late,
+    /// Surrender is also offered before the dealer checks for blackjack.
+    Early,
+}
+
+/// Every table rule the game reads from, loadable from a TOML file. Missing keys
+/// fall back to the Vegas Strip preset.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableRules {
+    pub name: String,
+    pub decks: u8,
+    /// Fraction of the shoe dealt before the cut card comes out.
+    pub penetration: f32,
+    pub dealer_hits_soft_17: bool,
+    pub blackjack_payout: BlackjackPayout,
+    pub double: DoubleRule,
+    pub double_after_split: bool,
+    pub surrender: SurrenderRule,
+    pub max_split_hands: usize,
+    pub hole_card: HoleCardRule,
+    /// Number of cards that wins automatically without busting, if the table plays a charlie rule.
+    pub charlie: Option<u8>,
+    /// The side bets offered alongside the main bet.
+    pub side_bets: Vec<SideBet>,
+}
+
+impl TableRules {
+    pub fn vegas_strip() -> TableRules {
+        TableRules {
+            name: String::from("Vegas Strip"),
+            decks: 6,
+            penetration: 0.75,
+            dealer_hits_soft_17: false,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            double: DoubleRule::AnyTwo,
+            double_after_split: true,
+            surrender: SurrenderRule::Late,
+            max_split_hands: 4,
+            hole_card: HoleCardRule::Peek,
+            charlie: None,
+            side_bets: Vec::new(),
+        }
+    }
+
+    pub fn atlantic_city() -> TableRules {
+        TableRules {
+            name: String::from("Atlantic City"),
+            decks: 8,
+            penetration: 0.7,
+            surrender: SurrenderRule::Late,
+            max_split_hands: 3,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    pub fn downtown_vegas() -> TableRules {
+        TableRules {
+            name: String::from("Downtown Vegas"),
+            decks: 2,
+            penetration: 0.65,
+            dealer_hits_soft_17: true,
+            surrender: SurrenderRule::NotAllowed,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    pub fn european() -> TableRules {
+        TableRules {
+            name: String::from("European"),
+            decks: 6,
+            penetration: 0.75,
+            double: DoubleRule::NineToEleven,
+            surrender: SurrenderRule::NotAllowed,
+            max_split_hands: 2,
+            hole_card: HoleCardRule::European,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    pub fn presets() -> Vec<TableRules> {
+        vec![
+            TableRules::vegas_strip(),
+            TableRules::atlantic_city(),
+            TableRules::downtown_vegas(),
+            TableRules::european(),
+        ]
+    }
+
+    pub fn from_toml(source: &str) -> Result<TableRules> {
+        let rules: TableRules = toml::from_str(source).map_err(|e| Error::Parse(e.to_string()))?;
+        rules.validate()?;
+
+        Ok(rules)
+    }
+
+    pub fn load(path: &str) -> Result<TableRules> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| Error::Io(format!("Could not read {}: {}", path, e)))?;
+
+        TableRules::from_toml(&source)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Table rules always serialize")
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !Shoe::DECK_COUNTS.contains(&self.decks) {
+            return Err(Error::InvalidRules(format!("a shoe must hold one of {:?} decks", Shoe::DECK_COUNTS)));
+        }
+        if !(0.5..=0.95).contains(&self.penetration) {
+            return Err(Error::InvalidRules(String::from("penetration must be between 0.5 and 0.95")));
+        }
+        if !(1..=4).contains(&self.max_split_hands) {
+            return Err(Error::InvalidRules(String::from("players can split into between 1 and 4 hands")));
+        }
+        if let Some(n) = self.charlie {
+            if !(5..=8).contains(&n) {
+                return Err(Error::InvalidRules(String::from("a charlie must be between 5 and 8 cards")));
+            }
+        }
+        for (i, side_bet) in self.side_bets.iter().enumerate() {
+            side_bet.validate()?;
+            if self.side_bets[..i].iter().any(|s| s.kind == side_bet.kind) {
+                return Err(Error::InvalidRules(format!("{} is offered more than once", side_bet.kind)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the dealer must draw to `hand` under the soft 17 rule.
+    pub fn dealer_hits(&self, hand: &Hand) -> bool {
+        let total = hand.total();
+        total < 17 || (self.dealer_hits_soft_17 && total == 17 && hand.is_soft())
+    }
+
+    /// The side bet of `kind`, if the table offers it.
+    pub fn side_bet(&self, kind: SideBetKind) -> Option<&SideBet> {
+        self.side_bets.iter().find(|s| s.kind == kind)
+    }
+
+    pub fn is_charlie(&self, hand: &Hand) -> bool {
+        match self.charlie {
+            Some(n) => hand.cards.len() >= n as usize && !hand.is_busted(),
+            None => false,
+        }
+    }
+}
+
+impl Default for TableRules {
+    fn default() -> TableRules {
+        TableRules::vegas_strip()
+    }
+}
+
+impl fmt::Display for TableRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let payout = match self.blackjack_payout {
+            BlackjackPayout::ThreeToTwo => "3:2",
+            BlackjackPayout::SixToFive => "6:5",
+            BlackjackPayout::EvenMoney => "1:1",
+        };
+        let double = match self.double {
+            DoubleRule::AnyTwo => "double any two",
+            DoubleRule::NineToEleven => "double 9-11",
+            DoubleRule::TenToEleven => "double 10-11",
+        };
+        let surrender = match self.surrender {
+            SurrenderRule::NotAllowed => "no surrender",
+            SurrenderRule::Late => "late surrender",
+            SurrenderRule::Early => "early surrender",
+        };
+        let hole_card = match self.hole_card {
+            HoleCardRule::Peek => "peek",
+            HoleCardRule::NoPeek => "no peek",
+            HoleCardRule::European => "no hole card",
+        };
+        write!(
+            f,
+            "{}: {} deck{}, {}, blackjack pays {}, {}{}, {}, split to {} hands, {}",
+            self.name,
+            self.decks,
+            if self.decks == 1 { "" } else { "s" },
+            if self.dealer_hits_soft_17 { "H17" } else { "S17" },
+            payout,
+            double,
+            if self.double_after_split { ", DAS" } else { "" },
+            surrender,
+            self.max_split_hands,
+            hole_card,
+        )?;
+        if let Some(n) = self.charlie {
+            write!(f, ", {}-card charlie", n)?;
+        }
+        if !self.side_bets.is_empty() {
+            let kinds: Vec<String> = self.side_bets.iter().map(|s| s.kind.to_string()).collect();
+            write!(f, ", side bets: {}", kinds.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::side_bet::{ Odds, SideBetOutcome };
+
+    pub fn dealer_peeks() {
+        assert!(HoleCardRule::Peek.dealer_peeks(&Card::_test_card(11)));
+        assert!(HoleCardRule::Peek.dealer_peeks(&Card::_test_card(10)));
+        assert!(!HoleCardRule::Peek.dealer_peeks(&Card::_test_card(9)));
+        assert!(!HoleCardRule::NoPeek.dealer_peeks(&Card::_test_card(11)));
+        assert!(!HoleCardRule::European.dealer_peeks(&Card::_test_card(10)));
+        assert!(HoleCardRule::Peek.deals_hole_card());
+        assert!(HoleCardRule::NoPeek.deals_hole_card());
+        assert!(!HoleCardRule::European.deals_hole_card());
+        assert_eq!(HoleCardRule::ALL.iter().filter(|r| r.deals_hole_card()).count(), 2);
+    }
+
+    pub fn check_presets() {
+        for rules in TableRules::presets() {
+            assert_eq!(rules.validate(), Ok(()));
+        }
+        assert_eq!(TableRules::default(), TableRules::vegas_strip());
+        assert!(TableRules::downtown_vegas().dealer_hits_soft_17);
+        assert_eq!(TableRules::european().hole_card, HoleCardRule::European);
+        assert_eq!(
+            format!("{}", TableRules::vegas_strip()),
+            "Vegas Strip: 6 decks, S17, blackjack pays 3:2, double any two, DAS, late surrender, split to 4 hands, peek",
+        );
+    }
+
+    pub fn pay_blackjack() {
+        assert_eq!(BlackjackPayout::ThreeToTwo.pay(10), 25);
+        assert_eq!(BlackjackPayout::SixToFive.pay(10), 22);
+        assert_eq!(BlackjackPayout::EvenMoney.pay(10), 20);
+        assert!(DoubleRule::AnyTwo.allows(5));
+        assert!(DoubleRule::NineToEleven.allows(9));
+        assert!(!DoubleRule::TenToEleven.allows(9));
+    }
+
+    pub fn load_rules_from_toml() {
+        let rules = TableRules::from_toml(r#"
+            name = "Home game"
+            decks = 2
+            dealer_hits_soft_17 = true
+            blackjack_payout = "6:5"
+            surrender = "early"
+            hole_card = "no-peek"
+            charlie = 5
+
+            [[side_bets]]
+            kind = "21+3"
+
+            [[side_bets]]
+            kind = "perfect-pairs"
+            pays = { perfect-pair = "30:1", mixed-pair = "5:1" }
+        "#).unwrap();
+        assert_eq!(rules.name, "Home game");
+        assert_eq!(rules.decks, 2);
+        assert_eq!(rules.blackjack_payout, BlackjackPayout::SixToFive);
+        assert_eq!(rules.surrender, SurrenderRule::Early);
+        assert_eq!(rules.hole_card, HoleCardRule::NoPeek);
+        assert_eq!(rules.charlie, Some(5));
+        assert_eq!(rules.double, DoubleRule::AnyTwo);
+        let perfect_pairs = rules.side_bet(SideBetKind::PerfectPairs).unwrap();
+        assert_eq!(perfect_pairs.odds(SideBetOutcome::PerfectPair), Some(Odds::new(30, 1)));
+        assert_eq!(perfect_pairs.odds(SideBetOutcome::ColoredPair), Some(Odds::new(12, 1)));
+        assert_eq!(rules.side_bet(SideBetKind::TwentyOnePlusThree), Some(&SideBet {
+            kind: SideBetKind::TwentyOnePlusThree,
+            pays: Default::default(),
+        }));
+        assert!(rules.to_string().ends_with("5-card charlie, side bets: 21+3, Perfect Pairs"));
+        assert_eq!(TableRules::from_toml(&rules.to_toml()), Ok(rules));
+        assert_eq!(
+            TableRules::from_toml("decks = 3"),
+            Err(Error::InvalidRules(String::from("a shoe must hold one of [1, 2, 4, 6, 8] decks"))),
+        );
+        assert!(TableRules::from_toml("shoes = 3").is_err());
+        assert!(TableRules::from_toml("blackjack_payout = \"2:1\"").is_err());
+        assert_eq!(
+            TableRules::from_toml("[[side_bets]]\nkind = \"21+3\"\n[[side_bets]]\nkind = \"21+3\""),
+            Err(Error::InvalidRules(String::from("21+3 is offered more than once"))),
+        );
+        assert!(TableRules::from_toml("[[side_bets]]\nkind = \"21+3\"\npays = { mixed-pair = \"6:1\" }").is_err());
+        assert!(TableRules::from_toml("[[side_bets]]\nkind = \"21+3\"\npays = { flush = \"5\" }").is_err());
+        assert!(TableRules::load("missing-rules.toml").is_err());
+    }
+
+    pub fn dealer_hits_soft_17() {
+        let mut hand = Hand::_test_hand(vec![11, 6]);
+        assert!(!TableRules::vegas_strip().dealer_hits(&hand));
+        assert!(TableRules::downtown_vegas().dealer_hits(&hand));
+        hand = Hand::_test_hand(vec![10, 7]);
+        assert!(!TableRules::downtown_vegas().dealer_hits(&hand));
+        hand = Hand::_test_hand(vec![10, 6]);
+        assert!(TableRules::vegas_strip().dealer_hits(&hand));
+        let mut rules = TableRules::vegas_strip();
+        rules.charlie = Some(5);
+        assert!(rules.is_charlie(&Hand::_test_hand(vec![2, 3, 2, 4, 2])));
+        assert!(!rules.is_charlie(&Hand::_test_hand(vec![10, 3, 2, 4, 5])));
+        assert!(!TableRules::vegas_strip().is_charlie(&Hand::_test_hand(vec![2, 3, 2, 4, 2])));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::count::Counter;
+use crate::error::{ Error, Result };
+use crate::player::PlayerList;
+use crate::progress::save_toml;
+use crate::rules::TableRules;
+use crate::shoe::Shoe;
+use crate::table::Table;
+use serde::{ Deserialize, Serialize };
+use std::fs;
+
+/// Writes a number as a string, since TOML integers stop at `i64::MAX`.
+mod as_string {
+    use serde::{ de, Deserialize, Deserializer, Serializer };
+
+    pub fn serialize<S: Serializer>(n: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&n.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|_| de::Error::custom(format!("'{}' is not a number", s)))
+    }
+}
+
+/// A game between rounds, saved so it can be picked up later exactly where it stopped:
+/// the same chips, round number and rules, and a shoe that deals the same cards.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SavedGame {
+    /// The save format, checked on loading. New fields need a serde default so older
+    /// saves still load.
+    pub version: u32,
+    #[serde(with = "as_string")]
+    pub seed: u64,
+    pub round: u32,
+    pub starting_chips: u32,
+    pub min_bet: u32,
+    /// Every player's chips, by seat.
+    pub chips: Vec<u32>,
+    /// How each seat is played, in the front-end's own words.
+    pub seats: Vec<String>,
+    /// The profile in each seat, empty for a guest.
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// The hand history the game is recorded to, if any.
+    pub history: Option<String>,
+    pub rules: TableRules,
+    pub shoe: Shoe,
+    pub counter: Option<Counter>,
+}
+
+impl SavedGame {
+    pub const PATH: &'static str = "blackjack_save.toml";
+    /// The format this version writes.
+    pub const VERSION: u32 = 1;
+
+    /// Saves `table` as it stands after a round, clearing the cards left on it into the
+    /// discards the way the next round would.
+    pub fn new(table: &Table, seed: u64, starting_chips: u32, seats: Vec<String>) -> SavedGame {
+        let mut table = table.clone();
+        table.clear_cards();
+
+        SavedGame {
+            version: SavedGame::VERSION,
+            seed,
+            round: table.round(),
+            starting_chips,
+            min_bet: table.min_bet(),
+            chips: table.players().iter().map(|p| p.chips.unwrap_or(0)).collect(),
+            seats,
+            names: table.players().iter().map(|p| p.name.clone().unwrap_or_default()).collect(),
+            history: None,
+            rules: table.rules().clone(),
+            shoe: table.shoe().clone(),
+            counter: None,
+        }
+    }
+
+    /// The table ready to start the next round.
+    pub fn table(&self) -> Table {
+        let mut players = PlayerList::from_chips(&self.chips);
+        for (player, name) in players.iter_mut().zip(self.names.iter()) {
+            player.name = Some(name.clone()).filter(|name| !name.is_empty());
+        }
+        Table::resume(self.rules.clone(), players, self.min_bet, self.shoe.clone(), self.round)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        save_toml(self, path)
+    }
+
+    /// Reads a save of this format or an older one.
+    pub fn load(path: &str) -> Result<SavedGame> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| Error::Io(format!("Could not read {}: {}", path, e)))?;
+        let value: toml::Table = toml::from_str(&source).map_err(|e| Error::Parse(e.to_string()))?;
+        let version = value.get("version").and_then(|v| v.as_integer()).unwrap_or(0);
+        // Older formats are upgraded here as the format changes.
+        match version {
+            1 => value.try_into().map_err(|e: toml::de::Error| Error::Parse(e.to_string())),
+            v if v > SavedGame::VERSION as i64 => {
+                Err(Error::Parse(format!("{} was saved by a newer version of the game", path)))
+            },
+            v => Err(Error::Parse(format!("{} has an unknown save format {}", path, v))),
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::count::CountSystem;
+    use crate::input::{ self, BasicStrategy, PlayerInput };
+    use crate::table::{ Event, Phase };
+
+    fn bots() -> Vec<Box<dyn PlayerInput>> {
+        (1..=3).map(|units| Box::new(BasicStrategy::new(units)) as Box<dyn PlayerInput>).collect()
+    }
+
+    fn play_rounds(table: &mut Table, seats: &mut [Box<dyn PlayerInput>], rounds: u32) -> Vec<Event> {
+        let mut events = Vec::new();
+        for _ in 0..rounds {
+            if !table.players_left() {
+                break;
+            }
+            events.extend(table.start_round().unwrap());
+            while table.phase() != Phase::RoundOver {
+                events.extend(input::step(table, seats).unwrap());
+            }
+        }
+
+        events
+    }
+
+    pub fn save_and_resume() {
+        let rules = TableRules::vegas_strip();
+        let shoe = Shoe::seeded(2, 0.75, u64::MAX);
+        let mut players = PlayerList::new(3, 200);
+        players[1].name = Some(String::from("Ada"));
+        let mut table = Table::with_shoe(rules, players, 10, shoe);
+        let mut seats = bots();
+        play_rounds(&mut table, &mut seats, 6);
+        let seat_names = vec![String::from("bot"); 3];
+        let saved = SavedGame {
+            counter: Some(Counter::new(CountSystem::HiLo, 2)),
+            history: Some(String::from("hand_histories/1.txt")),
+            ..SavedGame::new(&table, u64::MAX, 200, seat_names)
+        };
+        assert_eq!(saved.round, 6);
+        assert_eq!(saved.names, vec!["", "Ada", ""]);
+        assert_eq!(saved.chips, table.players().iter().map(|p| p.chips.unwrap()).collect::<Vec<u32>>());
+        assert_eq!(saved.shoe.remaining() + saved.shoe.discarded(), 104);
+
+        let path = std::env::temp_dir().join(format!("blackjack_save_{}.toml", std::process::id()));
+        let path = path.to_str().unwrap();
+        saved.save(path).unwrap();
+        let loaded = SavedGame::load(path).unwrap();
+        assert_eq!(loaded, saved);
+
+        // The resumed table plays on exactly as the original would have, shuffles included.
+        let mut resumed = loaded.table();
+        assert_eq!(resumed.round(), 6);
+        assert_eq!(format!("{}, {}", resumed.players()[0], resumed.players()[1]), "Player 1, Ada");
+        let expected = play_rounds(&mut table, &mut seats, 20);
+        assert!(expected.contains(&Event::Shuffled));
+        assert_eq!(play_rounds(&mut resumed, &mut bots(), 20), expected);
+
+        let source = std::fs::read_to_string(path).unwrap();
+        assert!(source.starts_with("version = 1\nseed = \"18446744073709551615\"\n"));
+        std::fs::write(path, source.replace("version = 1", "version = 2")).unwrap();
+        assert!(matches!(SavedGame::load(path), Err(Error::Parse(e)) if e.contains("newer version")));
+        // Saves from before profiles have no names.
+        let old = source.lines().filter(|line| !line.starts_with("names = ")).collect::<Vec<&str>>().join("\n");
+        std::fs::write(path, old).unwrap();
+        assert_eq!(SavedGame::load(path).unwrap().names, Vec::<String>::new());
+        std::fs::write(path, "version = 1\nseed = \"12\"\n").unwrap();
+        assert!(SavedGame::load(path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
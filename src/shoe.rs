@@ -0,0 +1,231 @@
+use crate::deck::{ Card, Deck };
+use crate::error::{ Error, Result };
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{ Deserialize, Serialize };
+
+/// Several decks dealt from across rounds, reshuffled once the cut card comes out.
+/// A saved shoe keeps its cards in order and its generator's state, so it deals and
+/// shuffles exactly as it would have.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Shoe {
+    deck: Deck,
+    discards: Vec<Card>,
+    decks: u8,
+    cut_card: usize,
+    needs_shuffle: bool,
+    #[serde(skip)]
+    refilled: bool,
+    #[serde(with = "rng_state")]
+    rng: ChaCha8Rng,
+}
+
+/// Saves a generator as its seed, stream and word position in hex, separated by colons.
+mod rng_state {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use serde::{ de, Deserialize, Deserializer, Serializer };
+
+    pub fn serialize<S: Serializer>(rng: &ChaCha8Rng, serializer: S) -> Result<S::Ok, S::Error> {
+        let seed: String = rng.get_seed().iter().map(|byte| format!("{:02x}", byte)).collect();
+        serializer.serialize_str(&format!("{}:{:x}:{:x}", seed, rng.get_stream(), rng.get_word_pos()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ChaCha8Rng, D::Error> {
+        let state = String::deserialize(deserializer)?;
+        let invalid = || de::Error::custom(format!("'{}' is not a generator state", state));
+        let parts: Vec<&str> = state.split(':').collect();
+        let [seed, stream, word_pos] = parts[..] else {
+            return Err(invalid());
+        };
+        if seed.len() != 64 || !seed.is_ascii() {
+            return Err(invalid());
+        }
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&seed[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+        }
+        let mut rng = ChaCha8Rng::from_seed(bytes);
+        rng.set_stream(u64::from_str_radix(stream, 16).map_err(|_| invalid())?);
+        rng.set_word_pos(u128::from_str_radix(word_pos, 16).map_err(|_| invalid())?);
+
+        Ok(rng)
+    }
+}
+
+impl Shoe {
+    pub const DECK_COUNTS: [u8; 5] = [1, 2, 4, 6, 8];
+
+    /// Builds a shuffled shoe of `decks` decks with the cut card placed after `penetration`
+    /// (a fraction between 0 and 1) of the cards.
+    pub fn new(decks: u8, penetration: f32) -> Shoe {
+        Shoe::with_rng(decks, penetration, ChaCha8Rng::from_entropy())
+    }
+
+    /// Builds a shoe whose every shuffle follows from `seed`, to replay a game exactly.
+    pub fn seeded(decks: u8, penetration: f32, seed: u64) -> Shoe {
+        Shoe::with_rng(decks, penetration, ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Builds a shoe like [`new`](Shoe::new) that shuffles with `rng`, so a seeded
+    /// generator deals the same cards every time.
+    pub fn with_rng(decks: u8, penetration: f32, rng: ChaCha8Rng) -> Shoe {
+        let size = decks as usize * 52;
+        let penetration = penetration.clamp(0.0, 1.0);
+        let mut shoe = Shoe {
+            deck: Deck::with_decks(decks),
+            discards: Vec::new(),
+            decks,
+            cut_card: size - (size as f32 * penetration) as usize,
+            needs_shuffle: false,
+            refilled: false,
+            rng,
+        };
+        shoe.shuffle();
+
+        shoe
+    }
+
+    /// Wraps a deck as a shoe without shuffling, dealing the cards in their current order.
+    pub fn stacked(deck: Deck) -> Shoe {
+        let decks = deck.len().div_ceil(52) as u8;
+        Shoe {
+            deck,
+            discards: Vec::new(),
+            decks,
+            cut_card: 0,
+            needs_shuffle: false,
+            refilled: false,
+            rng: ChaCha8Rng::from_entropy(),
+        }
+    }
+
+    pub fn decks(&self) -> u8 {
+        self.decks
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.deck.len()
+    }
+
+    pub fn discarded(&self) -> usize {
+        self.discards.len()
+    }
+
+    /// True once the cut card has come out; the shoe should be shuffled before the next round.
+    pub fn needs_shuffle(&self) -> bool {
+        self.needs_shuffle
+    }
+
+    /// True if the shoe ran dry and shuffled its discards back in since the last call,
+    /// so anything keeping a count of the cards seen should start again.
+    pub fn take_refill(&mut self) -> bool {
+        std::mem::take(&mut self.refilled)
+    }
+
+    /// Returns the discards to the shoe, shuffles it and burns the first card.
+    pub fn shuffle(&mut self) {
+        self.deck.extend(self.discards.drain(..).collect());
+        self.deck.shuffle(&mut self.rng);
+        self.discards.extend(self.deck.deal(1));
+        self.needs_shuffle = false;
+    }
+
+    pub fn discard(&mut self, cards: Vec<Card>) {
+        self.discards.extend(cards);
+    }
+
+    /// Deals `n` cards. If the shoe runs dry mid-round the discards are shuffled back in
+    /// so the round can go on. Fails without dealing if the shoe and its discards don't
+    /// hold `n` cards between them, as the rest are all on the table.
+    pub fn deal(&mut self, n: usize) -> Result<Vec<Card>> {
+        if n > self.deck.len() + self.discards.len() {
+            return Err(Error::ShoeEmpty);
+        }
+        let mut cards = self.deck.deal(n);
+        if cards.len() < n {
+            self.deck.extend(self.discards.drain(..).collect());
+            self.deck.shuffle(&mut self.rng);
+            self.needs_shuffle = true;
+            self.refilled = true;
+            cards.extend(self.deck.deal(n - cards.len()));
+        }
+        if self.deck.len() <= self.cut_card {
+            self.needs_shuffle = true;
+        }
+
+        Ok(cards)
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+
+    pub fn create_shoe() {
+        for decks in Shoe::DECK_COUNTS.iter() {
+            let shoe = Shoe::new(*decks, 0.75);
+            assert_eq!(shoe.decks(), *decks);
+            assert_eq!(shoe.remaining(), *decks as usize * 52 - 1);
+            assert_eq!(shoe.discarded(), 1);
+            assert!(!shoe.needs_shuffle());
+        }
+        let shoe = Shoe::stacked(Deck::new());
+        assert_eq!(shoe.remaining(), 52);
+        assert_eq!(shoe.decks(), 1);
+    }
+
+    pub fn reach_cut_card() {
+        let mut shoe = Shoe::new(2, 0.5);
+        let mut dealt = shoe.deal(50).unwrap();
+        assert!(!shoe.needs_shuffle());
+        dealt.extend(shoe.deal(1).unwrap());
+        assert!(shoe.needs_shuffle());
+        assert_eq!(shoe.remaining(), 52);
+        shoe.discard(dealt);
+        assert_eq!(shoe.discarded(), 52);
+        shoe.shuffle();
+        assert!(!shoe.needs_shuffle());
+        assert_eq!(shoe.remaining(), 103);
+        assert_eq!(shoe.discarded(), 1);
+    }
+
+    pub fn deal_from_dry_shoe() {
+        let mut shoe = Shoe::stacked(Deck::new());
+        let first = shoe.deal(50).unwrap();
+        shoe.discard(first);
+        assert!(!shoe.take_refill());
+        let cards = shoe.deal(5).unwrap();
+        assert_eq!(cards.len(), 5);
+        assert!(shoe.needs_shuffle());
+        assert!(shoe.take_refill());
+        assert!(!shoe.take_refill());
+        assert_eq!(shoe.remaining() + shoe.discarded(), 47);
+        // With every other card still on the table, the shoe deals nothing rather than
+        // add cards it never held.
+        let mut short_shoe = Shoe::stacked(Deck::new());
+        let on_table = short_shoe.deal(50).unwrap();
+        assert_eq!(short_shoe.deal(5), Err(Error::ShoeEmpty));
+        assert_eq!(short_shoe.remaining(), 2);
+        assert_eq!(Shoe::stacked(Deck::new()).deal(60), Err(Error::ShoeEmpty));
+    }
+
+    pub fn seeded_shoes() {
+        let mut shoe = Shoe::seeded(2, 0.5, 7);
+        let cards = shoe.deal(60).unwrap();
+        let names: Vec<String> = cards.iter().take(4).map(|c| c.to_string()).collect();
+        assert_eq!(names, vec!["Jack of Clubs", "9 of Clubs", "King of Hearts", "9 of Clubs"]);
+        shoe.discard(cards.clone());
+        shoe.shuffle();
+        let reshuffled = shoe.deal(60).unwrap();
+
+        let mut same = Shoe::seeded(2, 0.5, 7);
+        assert_eq!(same.deal(60).unwrap(), cards);
+        same.discard(cards.clone());
+        same.shuffle();
+        assert_eq!(same.deal(60).unwrap(), reshuffled);
+        assert_ne!(Shoe::seeded(2, 0.5, 8).deal(60).unwrap(), cards);
+    }
+}
@@ -0,0 +1,627 @@
+use crate::deck::{ Card, Deck, Value };
+use crate::error::{ Error, Result };
+use crate::hand::Hand;
+use crate::rules::TableRules;
+use serde::{ Deserialize, Serialize };
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// What a winning side bet pays, as chips won for chips staked, such as "25:1".
+/// Saved files hold odds the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Odds {
+    pub win: u32,
+    pub stake: u32,
+}
+
+impl Odds {
+    pub const fn new(win: u32, stake: u32) -> Odds {
+        Odds { win, stake }
+    }
+
+    /// Chips returned for a winning `amount`, including the stake. Part chips are kept
+    /// by the house, and a payout too large to count in chips is capped.
+    pub fn pay(&self, amount: u32) -> u32 {
+        let winnings = amount as u64 * self.win as u64 / self.stake as u64;
+        u32::try_from(amount as u64 + winnings).unwrap_or(u32::MAX)
+    }
+
+    /// Chips won for each chip staked.
+    pub fn value(&self) -> f64 {
+        self.win as f64 / self.stake as f64
+    }
+}
+
+impl fmt::Display for Odds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.win, self.stake)
+    }
+}
+
+impl FromStr for Odds {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Odds> {
+        let invalid = || Error::Parse(format!("'{}' is not odds such as 25:1", s));
+        let (win, stake) = s.split_once(':').ok_or_else(invalid)?;
+        let win = win.trim().parse().map_err(|_| invalid())?;
+        match stake.trim().parse() {
+            Ok(stake) if stake > 0 => Ok(Odds::new(win, stake)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl From<Odds> for String {
+    fn from(odds: Odds) -> String {
+        odds.to_string()
+    }
+}
+
+impl TryFrom<String> for Odds {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Odds> {
+        s.parse()
+    }
+}
+
+/// A winning result of a side bet, named in paytables the way rules files write them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SideBetOutcome {
+    /// Three of a kind, all of one suit.
+    SuitedTrips,
+    StraightFlush,
+    ThreeOfAKind,
+    /// Three ranks in a row, with the Ace high or low.
+    Straight,
+    Flush,
+    /// A pair of the same suit.
+    PerfectPair,
+    /// A pair of the same color in different suits.
+    ColoredPair,
+    /// A red and a black card of the same rank.
+    MixedPair,
+    /// A pair of Queens of Hearts against a dealer blackjack.
+    QueensOfHeartsDealerBlackjack,
+    QueensOfHearts,
+    /// Twenty with two cards of the same rank and suit.
+    MatchedTwenty,
+    SuitedTwenty,
+    AnyTwenty,
+    /// A total over 13, counting Aces as 1.
+    #[serde(rename = "over-13")]
+    OverThirteen,
+    /// A total under 13, counting Aces as 1.
+    #[serde(rename = "under-13")]
+    UnderThirteen,
+    /// A suited King and Queen.
+    RoyalMatch,
+    /// Any other two suited cards.
+    EasyMatch,
+    /// The dealer busts with eight cards or more.
+    EightCardBust,
+    SevenCardBust,
+    SixCardBust,
+    FiveCardBust,
+    /// The dealer busts with three or four cards.
+    ThreeOrFourCardBust,
+}
+
+impl SideBetOutcome {
+    pub const ALL: [SideBetOutcome; 22] = [
+        SideBetOutcome::SuitedTrips,
+        SideBetOutcome::StraightFlush,
+        SideBetOutcome::ThreeOfAKind,
+        SideBetOutcome::Straight,
+        SideBetOutcome::Flush,
+        SideBetOutcome::PerfectPair,
+        SideBetOutcome::ColoredPair,
+        SideBetOutcome::MixedPair,
+        SideBetOutcome::QueensOfHeartsDealerBlackjack,
+        SideBetOutcome::QueensOfHearts,
+        SideBetOutcome::MatchedTwenty,
+        SideBetOutcome::SuitedTwenty,
+        SideBetOutcome::AnyTwenty,
+        SideBetOutcome::OverThirteen,
+        SideBetOutcome::UnderThirteen,
+        SideBetOutcome::RoyalMatch,
+        SideBetOutcome::EasyMatch,
+        SideBetOutcome::EightCardBust,
+        SideBetOutcome::SevenCardBust,
+        SideBetOutcome::SixCardBust,
+        SideBetOutcome::FiveCardBust,
+        SideBetOutcome::ThreeOrFourCardBust,
+    ];
+}
+
+impl fmt::Display for SideBetOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SideBetOutcome::SuitedTrips => "suited trips",
+            SideBetOutcome::StraightFlush => "straight flush",
+            SideBetOutcome::ThreeOfAKind => "three of a kind",
+            SideBetOutcome::Straight => "straight",
+            SideBetOutcome::Flush => "flush",
+            SideBetOutcome::PerfectPair => "perfect pair",
+            SideBetOutcome::ColoredPair => "colored pair",
+            SideBetOutcome::MixedPair => "mixed pair",
+            SideBetOutcome::QueensOfHeartsDealerBlackjack => "queens of hearts with dealer blackjack",
+            SideBetOutcome::QueensOfHearts => "queens of hearts",
+            SideBetOutcome::MatchedTwenty => "matched 20",
+            SideBetOutcome::SuitedTwenty => "suited 20",
+            SideBetOutcome::AnyTwenty => "any 20",
+            SideBetOutcome::OverThirteen => "over 13",
+            SideBetOutcome::UnderThirteen => "under 13",
+            SideBetOutcome::RoyalMatch => "royal match",
+            SideBetOutcome::EasyMatch => "easy match",
+            SideBetOutcome::EightCardBust => "8+ card bust",
+            SideBetOutcome::SevenCardBust => "7 card bust",
+            SideBetOutcome::SixCardBust => "6 card bust",
+            SideBetOutcome::FiveCardBust => "5 card bust",
+            SideBetOutcome::ThreeOrFourCardBust => "3 or 4 card bust",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The side bets a table can offer. Most are settled on the cards dealt to start the
+/// round, the rest once the dealer has played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum SideBetKind {
+    /// The player's first two cards and the dealer's upcard as a three-card poker hand.
+    #[serde(rename = "21+3")]
+    TwentyOnePlusThree,
+    /// The player's first two cards as a pair.
+    #[serde(rename = "perfect-pairs")]
+    PerfectPairs,
+    /// The player's first two cards making 20, with a bonus for two Queens of Hearts.
+    #[serde(rename = "lucky-ladies")]
+    LuckyLadies,
+    /// The player's first two cards totalling over 13.
+    #[serde(rename = "over-13")]
+    OverThirteen,
+    /// The player's first two cards totalling under 13.
+    #[serde(rename = "under-13")]
+    UnderThirteen,
+    /// The player's first two cards being suited.
+    #[serde(rename = "royal-match")]
+    RoyalMatch,
+    /// The dealer busting, paying more the more cards it took.
+    #[serde(rename = "buster-blackjack")]
+    BusterBlackjack,
+}
+
+impl SideBetKind {
+    pub const ALL: [SideBetKind; 7] = [
+        SideBetKind::TwentyOnePlusThree,
+        SideBetKind::PerfectPairs,
+        SideBetKind::LuckyLadies,
+        SideBetKind::OverThirteen,
+        SideBetKind::UnderThirteen,
+        SideBetKind::RoyalMatch,
+        SideBetKind::BusterBlackjack,
+    ];
+
+    /// Whether the bet waits for the dealer's final hand rather than being settled on the deal.
+    pub fn settles_after_dealer(&self) -> bool {
+        matches!(self, SideBetKind::LuckyLadies | SideBetKind::BusterBlackjack)
+    }
+
+    /// Every outcome that pays, best first, with the odds most tables pay.
+    pub fn standard_paytable(&self) -> Vec<(SideBetOutcome, Odds)> {
+        match self {
+            SideBetKind::TwentyOnePlusThree => vec![
+                (SideBetOutcome::SuitedTrips, Odds::new(100, 1)),
+                (SideBetOutcome::StraightFlush, Odds::new(40, 1)),
+                (SideBetOutcome::ThreeOfAKind, Odds::new(30, 1)),
+                (SideBetOutcome::Straight, Odds::new(10, 1)),
+                (SideBetOutcome::Flush, Odds::new(5, 1)),
+            ],
+            SideBetKind::PerfectPairs => vec![
+                (SideBetOutcome::PerfectPair, Odds::new(25, 1)),
+                (SideBetOutcome::ColoredPair, Odds::new(12, 1)),
+                (SideBetOutcome::MixedPair, Odds::new(6, 1)),
+            ],
+            SideBetKind::LuckyLadies => vec![
+                (SideBetOutcome::QueensOfHeartsDealerBlackjack, Odds::new(1_000, 1)),
+                (SideBetOutcome::QueensOfHearts, Odds::new(125, 1)),
+                (SideBetOutcome::MatchedTwenty, Odds::new(19, 1)),
+                (SideBetOutcome::SuitedTwenty, Odds::new(9, 1)),
+                (SideBetOutcome::AnyTwenty, Odds::new(4, 1)),
+            ],
+            SideBetKind::OverThirteen => vec![(SideBetOutcome::OverThirteen, Odds::new(1, 1))],
+            SideBetKind::UnderThirteen => vec![(SideBetOutcome::UnderThirteen, Odds::new(1, 1))],
+            SideBetKind::RoyalMatch => vec![
+                (SideBetOutcome::RoyalMatch, Odds::new(25, 1)),
+                (SideBetOutcome::EasyMatch, Odds::new(5, 2)),
+            ],
+            SideBetKind::BusterBlackjack => vec![
+                (SideBetOutcome::EightCardBust, Odds::new(250, 1)),
+                (SideBetOutcome::SevenCardBust, Odds::new(50, 1)),
+                (SideBetOutcome::SixCardBust, Odds::new(12, 1)),
+                (SideBetOutcome::FiveCardBust, Odds::new(4, 1)),
+                (SideBetOutcome::ThreeOrFourCardBust, Odds::new(2, 1)),
+            ],
+        }
+    }
+
+    /// The best outcome of the `player`'s first two cards and the `dealer`'s hand, or
+    /// `None` if the bet loses. Bets settled on the deal see only the dealer's upcard.
+    pub fn outcome(&self, player: &[Card], dealer: &Hand) -> Option<SideBetOutcome> {
+        let [first, second] = player.get(..2)? else {
+            return None;
+        };
+        // Two cards can only make a hard total over 21 as a pair of Aces.
+        let total = first.numeric_value() + second.numeric_value();
+        match self {
+            SideBetKind::TwentyOnePlusThree => poker_hand([first, second, dealer.cards.first()?]),
+            SideBetKind::PerfectPairs if first.rank() != second.rank() => None,
+            SideBetKind::PerfectPairs if first.same_suit(second) => Some(SideBetOutcome::PerfectPair),
+            SideBetKind::PerfectPairs if first.is_red() == second.is_red() => Some(SideBetOutcome::ColoredPair),
+            SideBetKind::PerfectPairs => Some(SideBetOutcome::MixedPair),
+            SideBetKind::LuckyLadies if total != 20 => None,
+            SideBetKind::LuckyLadies => lucky_ladies(first, second, dealer.is_natural()),
+            SideBetKind::OverThirteen | SideBetKind::UnderThirteen => {
+                let hard_total = Hand::from_cards(vec![first.clone(), second.clone()]).hard_total();
+                match self {
+                    SideBetKind::OverThirteen => Some(SideBetOutcome::OverThirteen).filter(|_| hard_total > 13),
+                    _ => Some(SideBetOutcome::UnderThirteen).filter(|_| hard_total < 13),
+                }
+            },
+            SideBetKind::RoyalMatch if !first.same_suit(second) => None,
+            SideBetKind::RoyalMatch if matches!((first.rank(), second.rank()), (12, 13) | (13, 12)) => {
+                Some(SideBetOutcome::RoyalMatch)
+            },
+            SideBetKind::RoyalMatch => Some(SideBetOutcome::EasyMatch),
+            SideBetKind::BusterBlackjack => match dealer.cards.len() {
+                _ if !dealer.is_busted() => None,
+                n if n >= 8 => Some(SideBetOutcome::EightCardBust),
+                7 => Some(SideBetOutcome::SevenCardBust),
+                6 => Some(SideBetOutcome::SixCardBust),
+                5 => Some(SideBetOutcome::FiveCardBust),
+                _ => Some(SideBetOutcome::ThreeOrFourCardBust),
+            },
+        }
+    }
+}
+
+impl fmt::Display for SideBetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SideBetKind::TwentyOnePlusThree => "21+3",
+            SideBetKind::PerfectPairs => "Perfect Pairs",
+            SideBetKind::LuckyLadies => "Lucky Ladies",
+            SideBetKind::OverThirteen => "Over 13",
+            SideBetKind::UnderThirteen => "Under 13",
+            SideBetKind::RoyalMatch => "Royal Match",
+            SideBetKind::BusterBlackjack => "Buster Blackjack",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Reads three cards as a poker hand, counting only the hands 21+3 pays.
+fn poker_hand(cards: [&Card; 3]) -> Option<SideBetOutcome> {
+    let mut ranks = cards.map(|card| card.rank());
+    ranks.sort();
+    let flush = cards[0].same_suit(cards[1]) && cards[0].same_suit(cards[2]);
+    let trips = ranks[0] == ranks[2];
+    let straight = (ranks[0] + 1 == ranks[1] && ranks[1] + 1 == ranks[2]) || ranks == [1, 12, 13];
+    match (trips, straight, flush) {
+        (true, _, true) => Some(SideBetOutcome::SuitedTrips),
+        (_, true, true) => Some(SideBetOutcome::StraightFlush),
+        (true, _, _) => Some(SideBetOutcome::ThreeOfAKind),
+        (_, true, _) => Some(SideBetOutcome::Straight),
+        (_, _, true) => Some(SideBetOutcome::Flush),
+        _ => None,
+    }
+}
+
+/// Reads a player 20 for Lucky Ladies.
+fn lucky_ladies(first: &Card, second: &Card, dealer_blackjack: bool) -> Option<SideBetOutcome> {
+    let queen_of_hearts = |card: &Card| card.rank() == 12 && matches!(card.value(), Value::Hearts(_));
+    match (queen_of_hearts(first) && queen_of_hearts(second), first.same_suit(second)) {
+        (true, _) if dealer_blackjack => Some(SideBetOutcome::QueensOfHeartsDealerBlackjack),
+        (true, _) => Some(SideBetOutcome::QueensOfHearts),
+        (_, true) if first.rank() == second.rank() => Some(SideBetOutcome::MatchedTwenty),
+        (_, true) => Some(SideBetOutcome::SuitedTwenty),
+        _ => Some(SideBetOutcome::AnyTwenty),
+    }
+}
+
+/// A side bet a table offers, with what its outcomes pay.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SideBet {
+    pub kind: SideBetKind,
+    /// Odds for any outcome that pays differently from the standard paytable.
+    #[serde(default)]
+    pub pays: BTreeMap<SideBetOutcome, Odds>,
+}
+
+impl SideBet {
+    /// The bet on its standard paytable.
+    pub fn standard(kind: SideBetKind) -> SideBet {
+        SideBet { kind, pays: kind.standard_paytable().into_iter().collect() }
+    }
+
+    /// What `outcome` pays, or `None` if the bet has no such outcome.
+    pub fn odds(&self, outcome: SideBetOutcome) -> Option<Odds> {
+        self.pays.get(&outcome).copied().or_else(|| {
+            self.kind.standard_paytable().into_iter().find(|(o, _)| *o == outcome).map(|(_, odds)| odds)
+        })
+    }
+
+    /// Every outcome that pays, best first, with its odds.
+    pub fn paytable(&self) -> Vec<(SideBetOutcome, Odds)> {
+        self.kind.standard_paytable()
+            .into_iter()
+            .map(|(outcome, odds)| (outcome, self.pays.get(&outcome).copied().unwrap_or(odds)))
+            .collect()
+    }
+
+    /// Chips returned for `amount` staked on a bet that ended in `outcome`.
+    pub fn pay(&self, outcome: Option<SideBetOutcome>, amount: u32) -> u32 {
+        match outcome.and_then(|outcome| self.odds(outcome)) {
+            Some(odds) => odds.pay(amount),
+            None => 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let outcomes: Vec<SideBetOutcome> = self.kind.standard_paytable().into_iter().map(|(o, _)| o).collect();
+        match self.pays.keys().find(|outcome| !outcomes.contains(outcome)) {
+            Some(outcome) => Err(Error::InvalidRules(format!("{} has no {} outcome", self.kind, outcome))),
+            None => Ok(()),
+        }
+    }
+
+    /// The house edge per chip staked on a full shoe under `rules`, worked out exactly
+    /// over every way the player's first two cards and the dealer's cards can be dealt.
+    /// Bets settled after the dealer plays leave out any cards the players draw.
+    pub fn house_edge(&self, rules: &TableRules) -> f64 {
+        let cards = Deck::new().deal(52);
+        let copies = rules.decks as u64;
+        let left = |n: usize| copies.saturating_sub(n as u64);
+        let mut dealer_hands = BTreeMap::new();
+        let mut deals = 0.0;
+        let mut returned = 0.0;
+        for (a, first) in cards.iter().enumerate() {
+            for (b, second) in cards.iter().enumerate() {
+                let weight = (copies * left((a == b) as usize)) as f64;
+                if weight == 0.0 {
+                    continue;
+                }
+                let player = [first.clone(), second.clone()];
+                let mut bet = |dealer: &Hand, chance: f64| {
+                    let outcome = self.kind.outcome(&player, dealer);
+                    let won = outcome.and_then(|outcome| self.odds(outcome)).map_or(-1.0, |odds| odds.value());
+                    returned += weight * chance * won;
+                    deals += weight * chance;
+                };
+                if self.kind.settles_after_dealer() {
+                    // The dealer's draws only depend on the values the player holds.
+                    let mut values = [first.numeric_value(), second.numeric_value()];
+                    values.sort();
+                    let hands = dealer_hands.entry(values).or_insert_with(|| final_dealer_hands(rules, &player));
+                    for (dealer, chance) in hands.iter() {
+                        bet(dealer, *chance);
+                    }
+                } else {
+                    let shoe = (52 * copies - 2) as f64;
+                    for (c, upcard) in cards.iter().enumerate() {
+                        let dealer = Hand::from_cards(vec![upcard.clone()]);
+                        bet(&dealer, left((c == a) as usize + (c == b) as usize) as f64 / shoe);
+                    }
+                }
+            }
+        }
+
+        -returned / deals
+    }
+}
+
+/// Every final hand the dealer can draw under `rules` from a full shoe missing the
+/// `player`'s cards, with its chance. Cards are told apart only by value, and hands
+/// holding the same cards in a different order are counted as one.
+fn final_dealer_hands(rules: &TableRules, player: &[Card]) -> Vec<(Hand, f64)> {
+    let mut shoe: Vec<(Card, u64)> = Vec::new();
+    for card in Deck::new().deal(52) {
+        match shoe.iter_mut().find(|(c, _)| c.numeric_value() == card.numeric_value()) {
+            Some((_, count)) => *count += rules.decks as u64,
+            None => shoe.push((card, rules.decks as u64)),
+        }
+    }
+    for card in player {
+        if let Some((_, count)) = shoe.iter_mut().find(|(c, _)| c.numeric_value() == card.numeric_value()) {
+            *count = count.saturating_sub(1);
+        }
+    }
+    let total: u64 = shoe.iter().map(|(_, count)| count).sum();
+    // Whether the dealer draws again depends only on the cards held, not their order, so
+    // each set of cards is followed once with the chance of every order that reaches it.
+    let mut drawing: BTreeMap<Vec<usize>, f64> = BTreeMap::from([(Vec::new(), 1.0)]);
+    let mut hands = Vec::new();
+    while !drawing.is_empty() {
+        let mut next = BTreeMap::new();
+        for (drawn, chance) in drawing {
+            let hand = Hand::from_cards(drawn.iter().map(|&i| shoe[i].0.clone()).collect());
+            if !rules.dealer_hits(&hand) {
+                hands.push((hand, chance));
+                continue;
+            }
+            let left = total - drawn.len() as u64;
+            for (i, (_, count)) in shoe.iter().enumerate() {
+                let count = count - drawn.iter().filter(|&&d| d == i).count() as u64;
+                if count == 0 {
+                    continue;
+                }
+                let mut more = drawn.clone();
+                more.push(i);
+                more.sort();
+                *next.entry(more).or_insert(0.0) += chance * count as f64 / left as f64;
+            }
+        }
+        drawing = next;
+    }
+
+    hands
+}
+
+impl fmt::Display for SideBet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pays: Vec<String> = self.paytable()
+            .iter()
+            .map(|(outcome, odds)| format!("{} {}", outcome, odds))
+            .collect();
+        write!(f, "{} ({})", self.kind, pays.join(", "))
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+
+    fn cards(names: &[&str]) -> Vec<Card> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    fn outcome(kind: SideBetKind, names: &[&str]) -> Option<SideBetOutcome> {
+        let cards = cards(names);
+        kind.outcome(&cards[..2], &Hand::from_cards(cards[2..].to_vec()))
+    }
+
+    pub fn side_bet_outcomes() {
+        let twenty_one_plus_three = |names| outcome(SideBetKind::TwentyOnePlusThree, names);
+        assert_eq!(twenty_one_plus_three(&["7 of Hearts", "7 of Hearts", "7 of Hearts"]), Some(SideBetOutcome::SuitedTrips));
+        assert_eq!(twenty_one_plus_three(&["Queen of Clubs", "King of Clubs", "Ace of Clubs"]), Some(SideBetOutcome::StraightFlush));
+        assert_eq!(twenty_one_plus_three(&["7 of Hearts", "7 of Spades", "7 of Hearts"]), Some(SideBetOutcome::ThreeOfAKind));
+        assert_eq!(twenty_one_plus_three(&["3 of Hearts", "Ace of Spades", "2 of Hearts"]), Some(SideBetOutcome::Straight));
+        assert_eq!(twenty_one_plus_three(&["9 of Hearts", "Jack of Spades", "10 of Hearts"]), Some(SideBetOutcome::Straight));
+        assert_eq!(twenty_one_plus_three(&["2 of Diamonds", "9 of Diamonds", "King of Diamonds"]), Some(SideBetOutcome::Flush));
+        assert_eq!(twenty_one_plus_three(&["King of Hearts", "Ace of Spades", "2 of Hearts"]), None);
+        assert_eq!(twenty_one_plus_three(&["10 of Hearts", "King of Spades", "Jack of Hearts"]), None);
+
+        let perfect_pairs = |names| outcome(SideBetKind::PerfectPairs, names);
+        assert_eq!(perfect_pairs(&["8 of Spades", "8 of Spades", "2 of Hearts"]), Some(SideBetOutcome::PerfectPair));
+        assert_eq!(perfect_pairs(&["8 of Hearts", "8 of Diamonds", "2 of Hearts"]), Some(SideBetOutcome::ColoredPair));
+        assert_eq!(perfect_pairs(&["8 of Hearts", "8 of Clubs", "8 of Hearts"]), Some(SideBetOutcome::MixedPair));
+        assert_eq!(perfect_pairs(&["King of Hearts", "Queen of Hearts", "2 of Hearts"]), None);
+        assert_eq!(SideBetKind::PerfectPairs.outcome(&cards(&["8 of Hearts"]), &Hand::from_cards(cards(&["8 of Hearts"]))), None);
+
+        // Lucky Ladies sees the dealer's whole hand for its top prize.
+        let lucky_ladies = |names| outcome(SideBetKind::LuckyLadies, names);
+        assert_eq!(
+            lucky_ladies(&["Queen of Hearts", "Queen of Hearts", "Ace of Clubs", "King of Clubs"]),
+            Some(SideBetOutcome::QueensOfHeartsDealerBlackjack),
+        );
+        assert_eq!(
+            lucky_ladies(&["Queen of Hearts", "Queen of Hearts", "Ace of Clubs", "5 of Clubs", "5 of Clubs"]),
+            Some(SideBetOutcome::QueensOfHearts),
+        );
+        assert_eq!(lucky_ladies(&["Jack of Spades", "Jack of Spades", "9 of Hearts"]), Some(SideBetOutcome::MatchedTwenty));
+        assert_eq!(lucky_ladies(&["Ace of Hearts", "9 of Hearts", "9 of Hearts"]), Some(SideBetOutcome::SuitedTwenty));
+        assert_eq!(lucky_ladies(&["Queen of Hearts", "Queen of Diamonds", "9 of Hearts"]), Some(SideBetOutcome::AnyTwenty));
+        assert_eq!(lucky_ladies(&["Queen of Hearts", "9 of Hearts", "Ace of Hearts"]), None);
+
+        // Over and under 13 count Aces as 1, and 13 itself loses both.
+        let total = |kind, names| outcome(kind, names);
+        assert_eq!(total(SideBetKind::OverThirteen, &["Ace of Hearts", "King of Spades", "2 of Hearts"]), None);
+        assert_eq!(total(SideBetKind::UnderThirteen, &["Ace of Hearts", "King of Spades", "2 of Hearts"]), Some(SideBetOutcome::UnderThirteen));
+        assert_eq!(total(SideBetKind::OverThirteen, &["4 of Hearts", "King of Spades", "2 of Hearts"]), Some(SideBetOutcome::OverThirteen));
+        assert_eq!(total(SideBetKind::OverThirteen, &["3 of Hearts", "King of Spades", "2 of Hearts"]), None);
+        assert_eq!(total(SideBetKind::UnderThirteen, &["3 of Hearts", "King of Spades", "2 of Hearts"]), None);
+
+        let royal_match = |names| outcome(SideBetKind::RoyalMatch, names);
+        assert_eq!(royal_match(&["King of Clubs", "Queen of Clubs", "2 of Hearts"]), Some(SideBetOutcome::RoyalMatch));
+        assert_eq!(royal_match(&["King of Clubs", "Jack of Clubs", "2 of Hearts"]), Some(SideBetOutcome::EasyMatch));
+        assert_eq!(royal_match(&["King of Clubs", "Queen of Spades", "2 of Hearts"]), None);
+
+        // Buster Blackjack pays on the number of cards in a busted dealer hand.
+        let buster = |names| outcome(SideBetKind::BusterBlackjack, names);
+        assert_eq!(buster(&["2 of Hearts", "2 of Hearts", "10 of Clubs", "6 of Clubs", "King of Clubs"]), Some(SideBetOutcome::ThreeOrFourCardBust));
+        assert_eq!(
+            buster(&["2 of Hearts", "2 of Hearts", "2 of Clubs", "2 of Clubs", "2 of Spades", "2 of Spades", "5 of Spades", "King of Spades"]),
+            Some(SideBetOutcome::SixCardBust),
+        );
+        assert_eq!(buster(&["2 of Hearts", "2 of Hearts", "10 of Clubs", "7 of Clubs"]), None);
+        assert!(SideBetKind::BusterBlackjack.settles_after_dealer());
+        assert!(!SideBetKind::RoyalMatch.settles_after_dealer());
+    }
+
+    pub fn side_bet_paytables() {
+        assert_eq!("25:1".parse(), Ok(Odds::new(25, 1)));
+        assert_eq!("5 : 2".parse(), Ok(Odds::new(5, 2)));
+        assert!("25".parse::<Odds>().is_err());
+        assert!("1:0".parse::<Odds>().is_err());
+        assert_eq!(Odds::new(5, 2).pay(5), 17);
+        assert_eq!(Odds::new(1000, 1).pay(4_000_000), 4_004_000_000);
+        assert_eq!(Odds::new(1000, 1).pay(5_000_000), u32::MAX);
+        assert_eq!(SideBet::standard(SideBetKind::RoyalMatch).pay(Some(SideBetOutcome::EasyMatch), 10), 35);
+        assert_eq!(Odds::new(25, 1).to_string(), "25:1");
+
+        let standard = SideBet::standard(SideBetKind::PerfectPairs);
+        assert_eq!(format!("{}", standard), "Perfect Pairs (perfect pair 25:1, colored pair 12:1, mixed pair 6:1)");
+        assert_eq!(standard.pay(Some(SideBetOutcome::ColoredPair), 5), 65);
+        assert_eq!(standard.pay(None, 5), 0);
+        assert_eq!(standard.validate(), Ok(()));
+
+        // Outcomes left out of a paytable pay as on the standard one.
+        let custom = SideBet {
+            kind: SideBetKind::PerfectPairs,
+            pays: BTreeMap::from([(SideBetOutcome::PerfectPair, Odds::new(30, 1))]),
+        };
+        assert_eq!(custom.odds(SideBetOutcome::PerfectPair), Some(Odds::new(30, 1)));
+        assert_eq!(custom.odds(SideBetOutcome::MixedPair), Some(Odds::new(6, 1)));
+        assert_eq!(custom.odds(SideBetOutcome::Flush), None);
+        let wrong = SideBet { kind: SideBetKind::TwentyOnePlusThree, ..custom };
+        assert_eq!(wrong.validate(), Err(Error::InvalidRules(String::from("21+3 has no perfect pair outcome"))));
+    }
+
+    pub fn side_bet_house_edges() {
+        let edge = |kind: SideBetKind, pays: &[(SideBetOutcome, Odds)], decks: u8| {
+            SideBet { kind, pays: pays.iter().copied().collect() }.house_edge(&TableRules { decks, ..TableRules::vegas_strip() })
+        };
+        let exactly = |edge: f64, fraction: f64| assert!((edge - fraction).abs() < 1e-12, "{} is not {}", edge, fraction);
+        let standard = |kind: SideBetKind, rules: TableRules| SideBet::standard(kind).house_edge(&rules);
+        // 3.70% and 4.10% on eight decks, as published for these paytables.
+        exactly(edge(SideBetKind::TwentyOnePlusThree, &[], 8), 4_596.0 / 124_085.0);
+        exactly(edge(SideBetKind::TwentyOnePlusThree, &[], 6), 28_958.0 / 626_665.0);
+        exactly(edge(SideBetKind::TwentyOnePlusThree, &[], 1), 1_006.0 / 5_525.0);
+        exactly(edge(SideBetKind::PerfectPairs, &[], 8), 17.0 / 415.0);
+        exactly(edge(SideBetKind::PerfectPairs, &[], 6), 19.0 / 311.0);
+        // A single deck has no perfect pairs, so their odds make no difference there.
+        let perfect_pairs = [(SideBetOutcome::PerfectPair, Odds::new(1_000, 1))];
+        exactly(edge(SideBetKind::PerfectPairs, &perfect_pairs, 1), edge(SideBetKind::PerfectPairs, &[], 1));
+        exactly(edge(SideBetKind::PerfectPairs, &[], 1), 24.0 / 51.0);
+        // Making the straight pay 9:1 instead of 10:1 costs the player its 3.10% chance.
+        let straight = [(SideBetOutcome::Straight, Odds::new(9, 1))];
+        let difference = edge(SideBetKind::TwentyOnePlusThree, &straight, 6) - edge(SideBetKind::TwentyOnePlusThree, &[], 6);
+        assert!((difference - 0.031021).abs() < 5e-7);
+
+        // 6.54% over and 10.06% under on eight decks.
+        exactly(edge(SideBetKind::OverThirteen, &[], 8), 353.0 / 5_395.0);
+        exactly(edge(SideBetKind::UnderThirteen, &[], 8), 543.0 / 5_395.0);
+        exactly(edge(SideBetKind::OverThirteen, &[], 1), 15.0 / 221.0);
+        exactly(edge(SideBetKind::UnderThirteen, &[], 1), 67.0 / 663.0);
+        exactly(edge(SideBetKind::RoyalMatch, &[], 6), 539.0 / 8_086.0);
+        exactly(edge(SideBetKind::RoyalMatch, &[], 8), 697.0 / 10_790.0);
+        exactly(edge(SideBetKind::RoyalMatch, &[], 1), 24.0 / 221.0);
+
+        // The bets settled after the dealer plays add up millions of dealer hands, so they
+        // are checked to within rounding.
+        let closely = |edge: f64, fraction: f64| assert!((edge - fraction).abs() < 1e-10, "{} is not {}", edge, fraction);
+        let eight_decks = TableRules { decks: 8, ..TableRules::vegas_strip() };
+        closely(standard(SideBetKind::LuckyLadies, eight_decks.clone()), 3_520_823.0 / 14_642_030.0);
+        closely(standard(SideBetKind::BusterBlackjack, eight_decks), 0.08215372014435883);
+        // A dealer hitting soft 17 busts more often, but with fewer cards.
+        let hits_soft_17 = TableRules { decks: 6, dealer_hits_soft_17: true, ..TableRules::vegas_strip() };
+        closely(standard(SideBetKind::BusterBlackjack, hits_soft_17), 0.06214254721718673);
+    }
+}
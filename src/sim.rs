@@ -0,0 +1,445 @@
+use crate::count::{ CountSystem, Counter };
+use crate::deck::Card;
+use crate::error::{ Error, Result };
+use crate::hand::Hand;
+use crate::player::{ Action, BetResult, PlayerList };
+use crate::rules::TableRules;
+use crate::strategy;
+use crate::shoe::Shoe;
+use crate::table::{ Decision, Event, Phase, Table };
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
+use std::sync::mpsc;
+use std::thread;
+
+/// The table minimum in simulated games; results are reported in multiples of it.
+const UNIT: u32 = 10;
+/// A bankroll deep enough that a simulated player never runs out of chips.
+const BANKROLL: u32 = u32::MAX / 2;
+/// The z-score of a 95% confidence interval.
+const Z_95: f64 = 1.96;
+/// Hands played from each seed stream. Runs are split into chunks of this size however
+/// many threads play them.
+pub const CHUNK: u64 = 10_000;
+
+/// How a simulated player decides each hand.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimStrategy {
+    /// Basic strategy for the table rules, never taking insurance.
+    Basic,
+    /// Hits and stands like the dealer and never doubles, splits or surrenders.
+    MimicDealer,
+    /// Hits only hands that cannot bust, standing on soft 17 or more.
+    NeverBust,
+}
+
+impl SimStrategy {
+    pub const ALL: [SimStrategy; 3] = [SimStrategy::Basic, SimStrategy::MimicDealer, SimStrategy::NeverBust];
+
+    pub fn action(&self, hand: &Hand, upcard: &Card, rules: &TableRules, valid_moves: &[Action]) -> Action {
+        match self {
+            SimStrategy::Basic => strategy::best_action(hand, upcard, rules, valid_moves),
+            SimStrategy::MimicDealer if rules.dealer_hits(hand) => Action::Hit,
+            SimStrategy::NeverBust if hand.hard_total() < 12 && hand.total() < 17 => Action::Hit,
+            _ => Action::Stand,
+        }
+    }
+
+    /// The name used on the command line.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            SimStrategy::Basic => "basic",
+            SimStrategy::MimicDealer => "mimic-dealer",
+            SimStrategy::NeverBust => "never-bust",
+        }
+    }
+}
+
+impl fmt::Display for SimStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SimStrategy::Basic => "basic strategy",
+            SimStrategy::MimicDealer => "mimic the dealer",
+            SimStrategy::NeverBust => "never bust",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Bet sizes by true count. `units[n]` is the bet, in table minimums, at a true count of
+/// `n` rounded down; lower counts bet `units[0]` and higher ones the last entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BetSpread {
+    pub system: CountSystem,
+    pub units: Vec<u32>,
+}
+
+impl BetSpread {
+    pub fn flat() -> BetSpread {
+        BetSpread { system: CountSystem::HiLo, units: vec![1] }
+    }
+
+    /// Reads a comma separated ramp such as `1,2,4,8`, counted with Hi-Lo.
+    pub fn parse(source: &str) -> Result<BetSpread> {
+        let units = source.split(',')
+            .map(|n| match n.trim().parse::<u32>() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(Error::Parse(format!("'{}' is not a bet of one unit or more", n.trim()))),
+            })
+            .collect::<Result<Vec<u32>>>()?;
+
+        Ok(BetSpread { system: CountSystem::HiLo, units })
+    }
+
+    pub fn units(&self, true_count: f64) -> u32 {
+        let index = (true_count.floor().max(0.0) as usize).min(self.units.len() - 1);
+        self.units[index]
+    }
+}
+
+impl fmt::Display for BetSpread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.units.as_slice() {
+            [units] => write!(f, "flat {} unit(s)", units),
+            units => {
+                let ramp: Vec<String> = units.iter().map(|u| u.to_string()).collect();
+                write!(f, "{} by {} true count", ramp.join("-"), self.system)
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimOptions {
+    pub rules: TableRules,
+    pub hands: u64,
+    pub strategy: SimStrategy,
+    pub spread: BetSpread,
+    /// The master seed every chunk's shuffles are derived from.
+    pub seed: u64,
+    /// Worker threads, or 0 for one per core.
+    pub threads: usize,
+    /// Stops early once the 95% margin on the EV per hand is this tight, in units.
+    pub target_margin: Option<f64>,
+}
+
+impl SimOptions {
+    /// Checks the rules, and that there is at least one hand to play.
+    pub fn validate(&self) -> Result<()> {
+        self.rules.validate()?;
+        if self.hands == 0 {
+            return Err(Error::InvalidOptions(String::from("a simulation needs at least one hand")));
+        }
+
+        Ok(())
+    }
+}
+
+/// Totals from simulated hands. Every field is a sum, so results from separate runs
+/// can be added together.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimResult {
+    pub hands: u64,
+    /// Initial bets placed, in units.
+    pub wagered: u64,
+    /// Net winnings in chips, and the sum of each hand's net squared.
+    pub net: i64,
+    pub net_squared: u64,
+    /// Rounds classed by their net result, so a split that wins one hand and loses the
+    /// other is a push.
+    pub rounds_won: u64,
+    pub rounds_lost: u64,
+    pub rounds_pushed: u64,
+    pub blackjacks: u64,
+}
+
+impl SimResult {
+    /// Adds one round, a starting hand and any hands split from it, that won or lost `net`
+    /// chips on an initial bet of `units`.
+    pub fn record(&mut self, units: u32, net: i64, blackjack: bool) {
+        self.hands += 1;
+        self.wagered += units as u64;
+        self.net += net;
+        self.net_squared += (net * net) as u64;
+        match net {
+            n if n > 0 => self.rounds_won += 1,
+            n if n < 0 => self.rounds_lost += 1,
+            _ => self.rounds_pushed += 1,
+        }
+        self.blackjacks += blackjack as u64;
+    }
+
+    pub fn merge(&mut self, other: &SimResult) {
+        self.hands += other.hands;
+        self.wagered += other.wagered;
+        self.net += other.net;
+        self.net_squared += other.net_squared;
+        self.rounds_won += other.rounds_won;
+        self.rounds_lost += other.rounds_lost;
+        self.rounds_pushed += other.rounds_pushed;
+        self.blackjacks += other.blackjacks;
+    }
+
+    /// The mean result per hand, in units.
+    pub fn ev(&self) -> f64 {
+        self.net as f64 / UNIT as f64 / self.hands as f64
+    }
+
+    /// The standard deviation of one hand's result, in units.
+    pub fn std_dev(&self) -> f64 {
+        let n = self.hands as f64;
+        let mean = self.net as f64 / n;
+        let variance = (self.net_squared as f64 / n - mean * mean) * n / (n - 1.0).max(1.0);
+
+        variance.max(0.0).sqrt() / UNIT as f64
+    }
+
+    /// The half-width of the 95% confidence interval around [`ev`](SimResult::ev).
+    pub fn ev_margin(&self) -> f64 {
+        Z_95 * self.std_dev() / (self.hands as f64).sqrt()
+    }
+
+    /// Net winnings as a share of the initial bets, the player's edge.
+    pub fn edge(&self) -> f64 {
+        self.net as f64 / UNIT as f64 / self.wagered as f64
+    }
+
+    /// The share of hands counted by `count`, with its 95% margin.
+    pub fn frequency(&self, count: u64) -> (f64, f64) {
+        let p = count as f64 / self.hands as f64;
+
+        (p, Z_95 * (p * (1.0 - p) / self.hands as f64).sqrt())
+    }
+}
+
+impl fmt::Display for SimResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} hands, {} units bet", self.hands, self.wagered)?;
+        writeln!(f, "EV per hand: {:+.4} ± {:.4} units (95%)", self.ev(), self.ev_margin())?;
+        writeln!(f, "Player edge: {:+.3}% of the initial bet", 100.0 * self.edge())?;
+        write!(f, "Standard deviation: {:.3} units per hand", self.std_dev())?;
+        let frequencies = [
+            ("Rounds won", self.rounds_won),
+            ("Rounds lost", self.rounds_lost),
+            ("Rounds pushed", self.rounds_pushed),
+            ("Blackjacks", self.blackjacks),
+        ];
+        write!(f, "\nRounds are won, lost or pushed on their net result, with split hands together")?;
+        for (name, count) in frequencies {
+            let (p, margin) = self.frequency(count);
+            write!(f, "\n{}: {:.2}% ± {:.2}%", name, 100.0 * p, 100.0 * margin)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Plays the next round at `table` for a single seat, returning the units bet, the net
+/// chips won and whether the hand was a blackjack.
+fn play_round(table: &mut Table, options: &SimOptions, counter: &mut Counter) -> Result<(u32, i64, bool)> {
+    let before = table.players()[0].chips.unwrap() as i64;
+    let mut units = 0;
+    let mut blackjack = false;
+    let mut events = table.start_round()?;
+    loop {
+        for event in events.iter() {
+            counter.observe_event(event);
+            if let Event::HandSettled { result: BetResult::Blackjack(_), .. } | Event::EvenMoneyPaid { .. } = event {
+                blackjack = true;
+            }
+        }
+        if table.phase() == Phase::RoundOver {
+            break;
+        }
+        events = match table.next_decision() {
+            Some(Decision::Bet { seat }) => {
+                units = options.spread.units(counter.true_count(table.shoe()));
+                table.place_bet(seat, units * UNIT)?
+            },
+            Some(Decision::SideBets { seat }) => table.place_side_bets(seat, &[])?,
+            Some(Decision::Insurance { seat }) => table.insure(seat, 0)?,
+            Some(Decision::EvenMoney { seat }) => table.even_money(seat, false)?,
+            Some(Decision::EarlySurrender { seat }) => {
+                let accept = options.strategy == SimStrategy::Basic
+                    && strategy::early_surrender(table.players()[seat].hand(), table.upcard().unwrap());
+                table.early_surrender(seat, accept)?
+            },
+            Some(Decision::Action { seat, .. }) => {
+                let hand = table.players()[seat].hand();
+                let action = options.strategy.action(hand, table.upcard().unwrap(), table.rules(), &table.valid_moves());
+                table.act(action)?
+            },
+            None => table.advance()?,
+        };
+    }
+    let net = table.players()[0].chips.unwrap() as i64 - before;
+
+    Ok((units, net, blackjack))
+}
+
+/// Plays chunk `index` of a run: up to [`CHUNK`] hands from a fresh shoe, shuffled by
+/// stream `index` of the master seed. Chunks can be played in any order and merged.
+pub fn simulate_chunk(options: &SimOptions, index: u64) -> Result<SimResult> {
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    rng.set_stream(index);
+    let shoe = Shoe::with_rng(options.rules.decks, options.rules.penetration, rng);
+    let mut table = Table::with_shoe(options.rules.clone(), PlayerList::new(1, BANKROLL), UNIT, shoe);
+    let mut counter = Counter::new(options.spread.system, options.rules.decks);
+    let mut result = SimResult::default();
+    for _ in 0..CHUNK.min(options.hands.saturating_sub(index * CHUNK)) {
+        let (units, net, blackjack) = play_round(&mut table, options, &mut counter)?;
+        result.record(units, net, blackjack);
+    }
+
+    Ok(result)
+}
+
+/// Plays `options.hands` rounds of one seat against the dealer across worker threads,
+/// with no output. Chunks are merged in order and `progress` sees the total after each
+/// one, so the result depends only on the options, not on the number of threads.
+pub fn simulate<F: FnMut(&SimResult)>(options: &SimOptions, mut progress: F) -> Result<SimResult> {
+    options.validate()?;
+    let chunks = options.hands.div_ceil(CHUNK);
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let next = AtomicU64::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..threads.min(chunks as usize) {
+            let sender = sender.clone();
+            let (next, stop) = (&next, &stop);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= chunks || stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                if sender.send((index, simulate_chunk(options, index))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        let mut finished = BTreeMap::new();
+        let mut result = SimResult::default();
+        let mut merged = 0;
+        for (index, chunk) in receiver {
+            finished.insert(index, chunk);
+            while let Some(chunk) = finished.remove(&merged) {
+                let chunk = chunk.inspect_err(|_| stop.store(true, Ordering::Relaxed))?;
+                result.merge(&chunk);
+                merged += 1;
+                progress(&result);
+                if options.target_margin.is_some_and(|margin| result.ev_margin() <= margin) {
+                    stop.store(true, Ordering::Relaxed);
+                    return Ok(result);
+                }
+            }
+        }
+
+        Ok(result)
+    })
+}
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+
+    pub fn bet_spreads() {
+        let spread = BetSpread::parse("1, 2,4,8").unwrap();
+        assert_eq!(spread.units(-3.5), 1);
+        assert_eq!(spread.units(0.9), 1);
+        assert_eq!(spread.units(1.0), 2);
+        assert_eq!(spread.units(3.2), 8);
+        assert_eq!(spread.units(12.0), 8);
+        assert_eq!(format!("{}", spread), "1-2-4-8 by Hi-Lo true count");
+        assert_eq!(format!("{}", BetSpread::flat()), "flat 1 unit(s)");
+        assert!(BetSpread::parse("1,0").is_err());
+        assert!(BetSpread::parse("1,,2").is_err());
+    }
+
+    pub fn sim_statistics() {
+        let mut result = SimResult::default();
+        result.record(1, 10, false);
+        result.record(1, -10, false);
+        result.record(2, 30, true);
+        result.record(1, 0, false);
+        assert_eq!(result.frequency(result.rounds_won).0, 0.5);
+        assert_eq!((result.rounds_lost, result.rounds_pushed, result.blackjacks), (1, 1, 1));
+        assert!((result.ev() - 0.75).abs() < 1e-9);
+        assert!((result.edge() - 0.6).abs() < 1e-9);
+        // Results of 1, -1, 3 and 0 units: a sample variance of 2.9167.
+        assert!((result.std_dev() - 2.9167f64.sqrt()).abs() < 1e-3);
+
+        let mut merged = SimResult::default();
+        merged.merge(&result);
+        merged.merge(&result);
+        assert_eq!(merged.hands, 8);
+        assert_eq!(merged.net, 60);
+        assert_eq!(merged.ev(), result.ev());
+    }
+
+    fn options(hands: u64, seed: u64, threads: usize) -> SimOptions {
+        SimOptions {
+            rules: TableRules::vegas_strip(),
+            hands,
+            strategy: SimStrategy::Basic,
+            spread: BetSpread::flat(),
+            seed,
+            threads,
+            target_margin: None,
+        }
+    }
+
+    pub fn simulate_strategies() {
+        let options = options(50_000, 1, 0);
+        let basic = simulate(&options, |_| ()).unwrap();
+        assert_eq!(basic.hands, 50_000);
+        assert_eq!(basic.wagered, 50_000);
+        assert_eq!(basic.rounds_won + basic.rounds_lost + basic.rounds_pushed, 50_000);
+        // Basic strategy gives up around half a percent; 50,000 hands pin it within about 1%.
+        assert!(basic.ev().abs() < 0.02, "{}", basic);
+        assert!((1.0..1.3).contains(&basic.std_dev()), "{}", basic);
+        let (blackjacks, _) = basic.frequency(basic.blackjacks);
+        assert!((0.035..0.06).contains(&blackjacks), "{}", basic);
+
+        let never_bust = simulate(&SimOptions { strategy: SimStrategy::NeverBust, ..options.clone() }, |_| ()).unwrap();
+        assert!(never_bust.ev() < basic.ev(), "{}\n{}", never_bust, basic);
+    }
+
+    pub fn validate_options() {
+        assert_eq!(options(1, 1, 1).validate(), Ok(()));
+        let error = Error::InvalidOptions(String::from("a simulation needs at least one hand"));
+        assert_eq!(options(0, 1, 1).validate(), Err(error.clone()));
+        assert_eq!(simulate(&options(0, 1, 1), |_| ()), Err(error));
+        let rules = TableRules { decks: 3, ..TableRules::vegas_strip() };
+        assert!(matches!(SimOptions { rules, ..options(1, 1, 1) }.validate(), Err(Error::InvalidRules(_))));
+    }
+
+    pub fn reproducible_threads() {
+        let hands = 2 * CHUNK + 5_000;
+        let mut updates = Vec::new();
+        let single = simulate(&options(hands, 42, 1), |r| updates.push(r.hands)).unwrap();
+        assert_eq!(updates, vec![CHUNK, 2 * CHUNK, hands]);
+        let parallel = simulate(&options(hands, 42, 3), |_| ()).unwrap();
+        assert_eq!(single, parallel);
+        let mut chunks = SimResult::default();
+        for index in (0..3).rev() {
+            chunks.merge(&simulate_chunk(&options(hands, 42, 1), index).unwrap());
+        }
+        assert_eq!(chunks, single);
+        assert_ne!(simulate(&options(hands, 43, 3), |_| ()).unwrap(), single);
+
+        // One chunk pins the EV within about 0.022 units, so a 0.03 target stops after it.
+        let early = SimOptions { target_margin: Some(0.03), ..options(100 * CHUNK, 42, 2) };
+        let result = simulate(&early, |_| ()).unwrap();
+        assert_eq!(result.hands, CHUNK);
+        assert_eq!(result, simulate_chunk(&early, 0).unwrap());
+    }
+}
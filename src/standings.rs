@@ -0,0 +1,91 @@
+use blackjack::leaderboard;
+use blackjack::progress::parse_date;
+use blackjack::{ Profiles, Ranking, SessionFilter };
+
+/// Reads the flags both `leaderboard` and `sessions` take, returning the filter and the
+/// `--by` ranking if there was one.
+fn parse_filter(args: &[String]) -> Result<(SessionFilter, Option<Ranking>), String> {
+    let mut filter = SessionFilter::default();
+    let mut ranking = None;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("{} needs a value", flag))?;
+        let date = || parse_date(value).ok_or(format!("'{}' is not a date such as 2024-02-29", value));
+        match flag.as_str() {
+            "--from" => filter.from = Some(date()?),
+            // The whole of the last day counts.
+            "--to" => filter.before = Some(date()? + 86_400),
+            "--rules" => filter.rules = Some(value.clone()),
+            "--by" => {
+                ranking = Some(Ranking::ALL.into_iter()
+                    .find(|r| r.slug() == value)
+                    .ok_or(format!("'{}' is not one of net, roi, peak or streak", value))?);
+            },
+            _ => return Err(format!("Unknown option {}", flag)),
+        }
+    }
+
+    Ok((filter, ranking))
+}
+
+/// The saved profiles, or `None` after saying why there are none.
+fn load_profiles() -> Option<Profiles> {
+    match Profiles::load(Profiles::PATH) {
+        Ok(profiles) if profiles.profiles.is_empty() => {
+            println!("There are no profiles yet, make one when you start a game");
+            None
+        },
+        Ok(profiles) => Some(profiles),
+        Err(e) => {
+            println!("{}", e);
+            None
+        },
+    }
+}
+
+/// Ranks the profiles by the `--by` ranking, net winnings unless told otherwise. Errors
+/// are only for bad arguments.
+pub fn leaderboard(args: &[String]) -> Result<(), String> {
+    let (filter, ranking) = parse_filter(args)?;
+    let ranking = ranking.unwrap_or(Ranking::Net);
+    let Some(profiles) = load_profiles() else {
+        return Ok(());
+    };
+    let standings = leaderboard::leaderboard(&profiles, &filter, ranking);
+    if standings.is_empty() {
+        println!("No sessions match");
+        return Ok(());
+    }
+    println!("Leaderboard by {}", ranking);
+    for (i, standing) in standings.iter().enumerate() {
+        println!("{:>2}. {}", i + 1, standing);
+    }
+
+    Ok(())
+}
+
+/// Lists every session the named profile played, oldest first.
+pub fn sessions(args: &[String]) -> Result<(), String> {
+    let (name, flags) = args.split_first().ok_or("sessions needs a profile name")?;
+    let (filter, ranking) = parse_filter(flags)?;
+    if ranking.is_some() {
+        return Err(String::from("--by only ranks the leaderboard"));
+    }
+    let Some(profiles) = load_profiles() else {
+        return Ok(());
+    };
+    let Some(profile) = profiles.profiles.iter().find(|p| p.name.eq_ignore_ascii_case(name.trim())) else {
+        println!("There is no profile named {}", name);
+        return Ok(());
+    };
+    println!("{}\nLifetime: {}", profile, profile.stats);
+    let sessions: Vec<_> = profile.sessions.iter().filter(|s| filter.matches(s)).collect();
+    if sessions.is_empty() {
+        println!("No sessions match");
+    }
+    for session in sessions {
+        println!("{}", session);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::deck::Card;
+use crate::hand::Hand;
+use crate::player::Action;
+use crate::rules::{ HoleCardRule, TableRules };
+
+/// The basic strategy play for `hand` against the dealer's `upcard`, chosen from
+/// `valid_moves`. The charts are the multi-deck ones, adjusted for the soft 17 rule,
+/// double after split and a dealer who takes every bet on a blackjack without peeking.
+pub fn best_action(hand: &Hand, upcard: &Card, rules: &TableRules, valid_moves: &[Action]) -> Action {
+    let up = upcard.numeric_value();
+    let can = |action: Action| valid_moves.contains(&action);
+    let can_split = hand.is_pair() && can(Action::Split);
+
+    if can(Action::Surrender) && should_surrender(hand, up, rules, can_split) {
+        return Action::Surrender;
+    }
+    if can_split && should_split(hand.cards[0].numeric_value(), up, rules) {
+        return Action::Split;
+    }
+    let (action, fallback) = match hand.is_soft() {
+        true => soft_play(hand.total(), up, rules),
+        false => hard_play(hand.total(), up, rules),
+    };
+    match action {
+        Action::DoubleDown if !can(Action::DoubleDown) => fallback,
+        _ => action,
+    }
+}
+
+/// Whether to give up half the bet before the dealer checks for blackjack.
+pub fn early_surrender(hand: &Hand, upcard: &Card) -> bool {
+    let total = hand.total();
+    let pair = match hand.is_pair() {
+        true => hand.cards[0].numeric_value(),
+        false => 0,
+    };
+    if hand.is_soft() {
+        return false;
+    }
+    match upcard.numeric_value() {
+        11 => matches!(total, 5..=7 | 12..=17) || matches!(pair, 3 | 6 | 7 | 8),
+        10 => matches!(total, 14..=16) && pair != 8 || matches!(pair, 7 | 8),
+        _ => false,
+    }
+}
+
+/// True when a dealer blackjack takes doubles and splits as well as the original bet.
+fn loses_everything(rules: &TableRules) -> bool {
+    rules.hole_card == HoleCardRule::NoPeek
+}
+
+fn should_surrender(hand: &Hand, up: u8, rules: &TableRules, can_split: bool) -> bool {
+    if hand.is_soft() {
+        return false;
+    }
+    let h17 = rules.dealer_hits_soft_17;
+    if can_split && hand.cards[0].numeric_value() == 8 {
+        return match up {
+            11 => h17 || loses_everything(rules),
+            10 => loses_everything(rules),
+            _ => false,
+        };
+    }
+    match (hand.total(), up) {
+        (16, 9..=11) | (15, 10) => true,
+        (15, 11) | (17, 11) => h17,
+        _ => false,
+    }
+}
+
+fn should_split(pair: u8, up: u8, rules: &TableRules) -> bool {
+    let das = rules.double_after_split;
+    let against_ten_or_ace = up >= 10 && loses_everything(rules);
+    match pair {
+        11 => !(up == 11 && loses_everything(rules)),
+        8 => !against_ten_or_ace,
+        9 => matches!(up, 2..=6 | 8 | 9),
+        7 => up <= 7,
+        6 => matches!(up, 3..=6) || (up == 2 && das),
+        4 => matches!(up, 5 | 6) && das,
+        2 | 3 => matches!(up, 4..=7) || (up <= 3 && das),
+        _ => false,
+    }
+}
+
+/// The play for a soft total, and what to do instead when doubling is not allowed.
+fn soft_play(total: u8, up: u8, rules: &TableRules) -> (Action, Action) {
+    let h17 = rules.dealer_hits_soft_17;
+    match (total, up) {
+        (19, 6) if h17 => (Action::DoubleDown, Action::Stand),
+        (19.., _) => (Action::Stand, Action::Stand),
+        (18, 2) if h17 => (Action::DoubleDown, Action::Stand),
+        (18, 3..=6) => (Action::DoubleDown, Action::Stand),
+        (18, 2 | 7 | 8) => (Action::Stand, Action::Stand),
+        (17, 3..=6) | (15 | 16, 4..=6) | (13 | 14, 5 | 6) => (Action::DoubleDown, Action::Hit),
+        _ => (Action::Hit, Action::Hit),
+    }
+}
+
+/// The play for a hard total, and what to do instead when doubling is not allowed.
+fn hard_play(total: u8, up: u8, rules: &TableRules) -> (Action, Action) {
+    let double_ace = rules.dealer_hits_soft_17 && !loses_everything(rules);
+    match (total, up) {
+        (17.., _) => (Action::Stand, Action::Stand),
+        (13..=16, 2..=6) | (12, 4..=6) => (Action::Stand, Action::Stand),
+        (11, 2..=9) => (Action::DoubleDown, Action::Hit),
+        (11, 10) if !loses_everything(rules) => (Action::DoubleDown, Action::Hit),
+        (11, 11) if double_ace => (Action::DoubleDown, Action::Hit),
+        (10, 2..=9) | (9, 3..=6) => (Action::DoubleDown, Action::Hit),
+        _ => (Action::Hit, Action::Hit),
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+
+    const ALL: [Action; 5] = [Action::Hit, Action::Stand, Action::Surrender, Action::DoubleDown, Action::Split];
+    const NO_DOUBLE: [Action; 2] = [Action::Hit, Action::Stand];
+
+    fn advise(cards: Vec<u8>, up: u8, rules: &TableRules, valid_moves: &[Action]) -> Action {
+        best_action(&Hand::_test_hand(cards), &Card::_test_card(up), rules, valid_moves)
+    }
+
+    pub fn basic_strategy_plays() {
+        let s17 = TableRules::vegas_strip();
+        assert_eq!(advise(vec![8, 8], 10, &s17, &ALL), Action::Split);
+        assert_eq!(advise(vec![10, 10], 6, &s17, &ALL), Action::Stand);
+        assert_eq!(advise(vec![9, 9], 7, &s17, &ALL), Action::Stand);
+        assert_eq!(advise(vec![9, 9], 9, &s17, &ALL), Action::Split);
+        assert_eq!(advise(vec![5, 5], 6, &s17, &ALL), Action::DoubleDown);
+        assert_eq!(advise(vec![10, 6], 10, &s17, &ALL), Action::Surrender);
+        assert_eq!(advise(vec![10, 6], 10, &s17, &NO_DOUBLE), Action::Hit);
+        assert_eq!(advise(vec![10, 2], 3, &s17, &ALL), Action::Hit);
+        assert_eq!(advise(vec![10, 2], 4, &s17, &ALL), Action::Stand);
+        assert_eq!(advise(vec![6, 5], 10, &s17, &ALL), Action::DoubleDown);
+        assert_eq!(advise(vec![6, 5], 11, &s17, &ALL), Action::Hit);
+        assert_eq!(advise(vec![6, 5], 6, &s17, &NO_DOUBLE), Action::Hit);
+        assert_eq!(advise(vec![11, 7], 4, &s17, &ALL), Action::DoubleDown);
+        assert_eq!(advise(vec![11, 7], 4, &s17, &NO_DOUBLE), Action::Stand);
+        assert_eq!(advise(vec![11, 7], 10, &s17, &ALL), Action::Hit);
+        assert_eq!(advise(vec![11, 2, 4], 6, &s17, &NO_DOUBLE), Action::Hit);
+    }
+
+    pub fn strategy_follows_rules() {
+        let s17 = TableRules::vegas_strip();
+        let h17 = TableRules { dealer_hits_soft_17: true, ..TableRules::vegas_strip() };
+        let no_das = TableRules { double_after_split: false, ..TableRules::vegas_strip() };
+        let no_peek = TableRules { hole_card: HoleCardRule::NoPeek, ..TableRules::vegas_strip() };
+        assert_eq!(advise(vec![6, 5], 11, &h17, &ALL), Action::DoubleDown);
+        assert_eq!(advise(vec![11, 8], 6, &s17, &ALL), Action::Stand);
+        assert_eq!(advise(vec![11, 8], 6, &h17, &ALL), Action::DoubleDown);
+        assert_eq!(advise(vec![10, 5], 11, &s17, &ALL), Action::Hit);
+        assert_eq!(advise(vec![10, 5], 11, &h17, &ALL), Action::Surrender);
+        assert_eq!(advise(vec![10, 7], 11, &h17, &ALL), Action::Surrender);
+        assert_eq!(advise(vec![8, 8], 11, &s17, &ALL), Action::Split);
+        assert_eq!(advise(vec![8, 8], 11, &h17, &ALL), Action::Surrender);
+        assert_eq!(advise(vec![2, 2], 2, &s17, &ALL), Action::Split);
+        assert_eq!(advise(vec![2, 2], 2, &no_das, &ALL), Action::Hit);
+        assert_eq!(advise(vec![4, 4], 5, &no_das, &ALL), Action::Hit);
+        assert_eq!(advise(vec![6, 5], 10, &no_peek, &ALL), Action::Hit);
+        assert_eq!(advise(vec![8, 8], 10, &no_peek, &ALL), Action::Surrender);
+        assert_eq!(advise(vec![8, 8], 10, &no_peek, &NO_DOUBLE), Action::Hit);
+        assert!(early_surrender(&Hand::_test_hand(vec![10, 6]), &Card::_test_card(10)));
+        assert!(early_surrender(&Hand::_test_hand(vec![8, 8]), &Card::_test_card(11)));
+        assert!(!early_surrender(&Hand::_test_hand(vec![10, 8]), &Card::_test_card(10)));
+        assert!(!early_surrender(&Hand::_test_hand(vec![11, 6]), &Card::_test_card(11)));
+    }
+}
@@ -0,0 +1,856 @@
+use crate::deck::Card;
+use crate::error::{ Error, Result };
+use crate::hand::Hand;
+use crate::player::{ Action, BetResult, Player, PlayerList };
+use crate::round;
+use crate::rules::{ HoleCardRule, SurrenderRule, TableRules };
+use crate::shoe::Shoe;
+use crate::side_bet::{ SideBetKind, SideBetOutcome };
+use std::collections::VecDeque;
+
+/// The stages a round moves through, in order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Betting,
+    Dealing,
+    Insurance,
+    PlayerTurns,
+    DealerTurn,
+    Settlement,
+    /// Between rounds: the previous round is settled and its cards are still on the table.
+    RoundOver,
+}
+
+/// A decision the table is waiting on before it can carry on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decision {
+    Bet { seat: usize },
+    /// Which of the table's side bets to place alongside the bet just made.
+    SideBets { seat: usize },
+    Insurance { seat: usize },
+    EvenMoney { seat: usize },
+    EarlySurrender { seat: usize },
+    Action { seat: usize, hand: usize },
+}
+
+/// Something that happened at the table, for front-ends to display or record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    RoundStarted { round: u32 },
+    Shuffled,
+    /// The shoe ran dry mid-round and its discards were shuffled back in.
+    ShoeRefilled,
+    BetPlaced { seat: usize, amount: u32 },
+    SideBetPlaced { seat: usize, kind: SideBetKind, amount: u32 },
+    PlayerCard { seat: usize, hand: usize, card: Card, total: u8 },
+    DealerUpcard { card: Card },
+    DealerHoleCard,
+    /// A side bet was settled on the dealt cards; `outcome` is `None` when it lost.
+    SideBetSettled { seat: usize, kind: SideBetKind, outcome: Option<SideBetOutcome>, payout: u32 },
+    InsuranceOpen,
+    InsuranceTaken { seat: usize, amount: u32 },
+    EvenMoneyPaid { seat: usize, payout: u32 },
+    Surrendered { seat: usize, hand: usize, refund: u32 },
+    DealerPeeked { blackjack: bool },
+    InsuranceSettled { seat: usize, payout: u32 },
+    TurnStarted { seat: usize, hand: usize },
+    ActionTaken { seat: usize, hand: usize, action: Action },
+    HandBusted { seat: usize, hand: usize, total: u8 },
+    Charlie { seat: usize, hand: usize },
+    HoleCardRevealed { card: Card, total: u8 },
+    DealerCard { card: Card, total: u8 },
+    DealerStands { total: u8 },
+    DealerBusts { total: u8 },
+    HandSettled { seat: usize, hand: usize, result: BetResult, payout: u32 },
+    OriginalBetsOnly { seat: usize, refund: u32 },
+    RoundEnded,
+}
+
+impl Event {
+    /// The seat the event belongs to, or `None` for the dealer and the round as a whole.
+    pub fn seat(&self) -> Option<usize> {
+        match self {
+            Event::BetPlaced { seat, .. }
+            | Event::SideBetPlaced { seat, .. }
+            | Event::SideBetSettled { seat, .. }
+            | Event::PlayerCard { seat, .. }
+            | Event::InsuranceTaken { seat, .. }
+            | Event::EvenMoneyPaid { seat, .. }
+            | Event::Surrendered { seat, .. }
+            | Event::InsuranceSettled { seat, .. }
+            | Event::TurnStarted { seat, .. }
+            | Event::ActionTaken { seat, .. }
+            | Event::HandBusted { seat, .. }
+            | Event::Charlie { seat, .. }
+            | Event::HandSettled { seat, .. }
+            | Event::OriginalBetsOnly { seat, .. } => Some(*seat),
+            _ => None,
+        }
+    }
+}
+
+/// A blackjack table that plays rounds as a state machine. Front-ends ask for the
+/// [`next_decision`](Table::next_decision), answer it, and call [`advance`](Table::advance)
+/// whenever no decision is pending. Every call returns the events it caused.
+#[derive(Clone)]
+pub struct Table {
+    rules: TableRules,
+    shoe: Shoe,
+    players: PlayerList,
+    dealer: Player,
+    min_bet: u32,
+    round: u32,
+    phase: Phase,
+    pending: VecDeque<Decision>,
+    turn: usize,
+    original_bets_only: bool,
+    /// Each seat's first two cards, which side bets are settled on even after a split.
+    opening_cards: Vec<Vec<Card>>,
+}
+
+impl Table {
+    pub fn new(rules: TableRules, players: PlayerList, min_bet: u32) -> Table {
+        let shoe = Shoe::new(rules.decks, rules.penetration);
+        Table::with_shoe(rules, players, min_bet, shoe)
+    }
+
+    /// Seats the players at a table dealing from an existing shoe.
+    pub fn with_shoe(rules: TableRules, players: PlayerList, min_bet: u32, shoe: Shoe) -> Table {
+        Table {
+            rules,
+            shoe,
+            players,
+            dealer: Player::new(0, 0),
+            min_bet,
+            round: 0,
+            phase: Phase::RoundOver,
+            pending: VecDeque::new(),
+            turn: 0,
+            original_bets_only: false,
+            opening_cards: Vec::new(),
+        }
+    }
+
+    /// Seats the players at a table that has already played `round` rounds from `shoe`,
+    /// as when a saved game is picked up again.
+    pub fn resume(rules: TableRules, players: PlayerList, min_bet: u32, shoe: Shoe, round: u32) -> Table {
+        Table { round, ..Table::with_shoe(rules, players, min_bet, shoe) }
+    }
+
+    pub fn rules(&self) -> &TableRules {
+        &self.rules
+    }
+
+    pub fn shoe(&self) -> &Shoe {
+        &self.shoe
+    }
+
+    pub fn players(&self) -> &PlayerList {
+        &self.players
+    }
+
+    pub fn dealer(&self) -> &Player {
+        &self.dealer
+    }
+
+    pub fn min_bet(&self) -> u32 {
+        self.min_bet
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn upcard(&self) -> Option<&Card> {
+        self.dealer.hand().cards.first()
+    }
+
+    pub fn players_left(&self) -> bool {
+        self.players.players_left()
+    }
+
+    /// The decision the table is waiting on, if any.
+    pub fn next_decision(&self) -> Option<Decision> {
+        match self.phase {
+            Phase::Betting | Phase::Insurance => self.pending.front().copied(),
+            Phase::PlayerTurns => Some(Decision::Action {
+                seat: self.turn,
+                hand: self.players[self.turn].active_hand(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The moves open to the hand whose turn it is.
+    pub fn valid_moves(&self) -> Vec<Action> {
+        match self.phase {
+            Phase::PlayerTurns => self.players[self.turn].valid_moves(&self.rules),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Clears the last round's cards, shuffles if the cut card came out and opens betting.
+    pub fn start_round(&mut self) -> Result<Vec<Event>> {
+        if self.phase != Phase::RoundOver {
+            return Err(Error::WrongPhase);
+        }
+        if !self.players.players_left() {
+            return Err(Error::NoPlayersLeft);
+        }
+        let mut events = Vec::new();
+        self.clear_cards();
+        self.round += 1;
+        self.original_bets_only = false;
+        events.push(Event::RoundStarted { round: self.round });
+        if self.shoe.needs_shuffle() {
+            self.shoe.shuffle();
+            events.push(Event::Shuffled);
+        }
+        self.pending = self.players.iter()
+            .enumerate()
+            .filter(|(_, p)| !p.is_broke())
+            .map(|(seat, _)| Decision::Bet { seat })
+            .collect();
+        self.phase = Phase::Betting;
+
+        Ok(events)
+    }
+
+    pub fn place_bet(&mut self, seat: usize, amount: u32) -> Result<Vec<Event>> {
+        self.expect_decision(Decision::Bet { seat })?;
+        let player = &mut self.players[seat];
+        if amount < self.min_bet && amount < player.chips.unwrap_or(0) {
+            return Err(Error::BetBelowMinimum);
+        }
+        if amount == 0 {
+            return Err(Error::BetBelowMinimum);
+        }
+        player.bet(amount)?;
+        self.pending.pop_front();
+        if !self.rules.side_bets.is_empty() && !self.players[seat].is_broke() {
+            self.pending.push_front(Decision::SideBets { seat });
+        }
+
+        Ok(vec![Event::BetPlaced { seat, amount }])
+    }
+
+    /// Stakes each of `bets` for `seat` straight after its main bet; none declines. Either
+    /// every bet is placed or, on an error, none is.
+    pub fn place_side_bets(&mut self, seat: usize, bets: &[(SideBetKind, u32)]) -> Result<Vec<Event>> {
+        self.expect_decision(Decision::SideBets { seat })?;
+        if bets.iter().any(|(kind, _)| self.rules.side_bet(*kind).is_none()) {
+            return Err(Error::SideBetNotOffered);
+        }
+        let mut player = self.players[seat].clone();
+        let mut events = Vec::new();
+        for &(kind, amount) in bets.iter().filter(|(_, amount)| *amount > 0) {
+            player.place_side_bet(kind, amount)?;
+            events.push(Event::SideBetPlaced { seat, kind, amount });
+        }
+        self.players[seat] = player;
+        self.pending.pop_front();
+
+        Ok(events)
+    }
+
+    /// Stakes `amount` on insurance for `seat`; zero declines.
+    pub fn insure(&mut self, seat: usize, amount: u32) -> Result<Vec<Event>> {
+        self.expect_decision(Decision::Insurance { seat })?;
+        let mut events = Vec::new();
+        if amount > 0 {
+            self.players[seat].insure(amount)?;
+            events.push(Event::InsuranceTaken { seat, amount });
+        }
+        self.pending.pop_front();
+
+        Ok(events)
+    }
+
+    pub fn even_money(&mut self, seat: usize, accept: bool) -> Result<Vec<Event>> {
+        self.expect_decision(Decision::EvenMoney { seat })?;
+        let mut events = Vec::new();
+        if accept {
+            let payout = self.players[seat].resolve_bet(BetResult::EvenMoney)?;
+            events.push(Event::EvenMoneyPaid { seat, payout });
+        }
+        self.pending.pop_front();
+
+        Ok(events)
+    }
+
+    pub fn early_surrender(&mut self, seat: usize, accept: bool) -> Result<Vec<Event>> {
+        self.expect_decision(Decision::EarlySurrender { seat })?;
+        let mut events = Vec::new();
+        if accept {
+            let refund = self.players[seat].resolve_bet(BetResult::Surrender)?;
+            events.push(Event::Surrendered { seat, hand: 0, refund });
+        }
+        self.pending.pop_front();
+
+        Ok(events)
+    }
+
+    /// Plays `action` on the hand whose turn it is.
+    pub fn act(&mut self, action: Action) -> Result<Vec<Event>> {
+        if self.phase != Phase::PlayerTurns {
+            return Err(Error::WrongPhase);
+        }
+        if !self.valid_moves().contains(&action) {
+            return Err(Error::IllegalAction);
+        }
+        let seat = self.turn;
+        let player = &mut self.players[seat];
+        let hand = player.active_hand();
+        let mut events = vec![Event::ActionTaken { seat, hand, action: action.clone() }];
+        match action {
+            Action::Hit => {
+                player.get_cards(&mut self.shoe, 1)?;
+            },
+            Action::Stand => (),
+            Action::Surrender => {
+                let refund = player.resolve_bet(BetResult::Surrender)?;
+                events.push(Event::Surrendered { seat, hand, refund });
+            },
+            Action::DoubleDown => {
+                player.double_down();
+                player.get_cards(&mut self.shoe, 1)?;
+            },
+            Action::Split => {
+                player.split()?;
+                player.get_cards(&mut self.shoe, 1)?;
+            },
+        }
+        if matches!(action, Action::Hit | Action::DoubleDown | Action::Split) {
+            Table::note_refill(&mut self.shoe, &mut events);
+            events.push(Event::PlayerCard {
+                seat,
+                hand,
+                card: player.latest_card().clone(),
+                total: player.hand_total(),
+            });
+        }
+        if player.hand().is_busted() {
+            let total = player.hand_total();
+            player.resolve_bet(BetResult::Lose)?;
+            events.push(Event::HandBusted { seat, hand, total });
+        } else if self.rules.is_charlie(player.hand()) {
+            events.push(Event::Charlie { seat, hand });
+        }
+        if action == Action::Stand || !Table::needs_decision(player.hand(), &self.rules) {
+            if !player.next_hand() {
+                self.turn += 1;
+            }
+            self.find_turn(&mut events)?;
+        }
+
+        Ok(events)
+    }
+
+    /// Runs the next automatic step of the round. Fails if a decision is still pending, or
+    /// with [`Error::ShoeEmpty`] if every card is on the table and the round can't go on.
+    pub fn advance(&mut self) -> Result<Vec<Event>> {
+        if self.next_decision().is_some() {
+            return Err(Error::NotYourTurn);
+        }
+        let mut events = Vec::new();
+        match self.phase {
+            Phase::Betting => self.phase = Phase::Dealing,
+            Phase::Dealing => self.deal(&mut events)?,
+            Phase::Insurance => self.check_dealer(&mut events)?,
+            Phase::DealerTurn => self.play_dealer(&mut events)?,
+            Phase::Settlement => self.settle(&mut events),
+            Phase::PlayerTurns | Phase::RoundOver => return Err(Error::WrongPhase),
+        }
+
+        Ok(events)
+    }
+
+    /// Moves every card on the table to the discards.
+    pub(crate) fn clear_cards(&mut self) {
+        self.shoe.discard(self.players.clear_cards());
+        self.shoe.discard(self.dealer.clear_cards());
+    }
+
+    fn expect_decision(&self, decision: Decision) -> Result<()> {
+        match self.next_decision() {
+            Some(pending) if pending == decision => Ok(()),
+            Some(_) => Err(Error::NotYourTurn),
+            None => Err(Error::WrongPhase),
+        }
+    }
+
+    fn deal(&mut self, events: &mut Vec<Event>) -> Result<()> {
+        let seats: Vec<usize> = (0..self.players.len())
+            .filter(|&seat| self.players[seat].is_in_pot())
+            .collect();
+        for second_card in [false, true] {
+            for &seat in seats.iter() {
+                let player = &mut self.players[seat];
+                player.get_cards(&mut self.shoe, 1)?;
+                Table::note_refill(&mut self.shoe, events);
+                events.push(Event::PlayerCard {
+                    seat,
+                    hand: 0,
+                    card: player.latest_card().clone(),
+                    total: player.hand_total(),
+                });
+            }
+            if !second_card {
+                self.dealer.get_cards(&mut self.shoe, 1)?;
+                Table::note_refill(&mut self.shoe, events);
+                events.push(Event::DealerUpcard { card: self.dealer.latest_card().clone() });
+            } else if self.rules.hole_card.deals_hole_card() {
+                self.dealer.get_cards(&mut self.shoe, 1)?;
+                Table::note_refill(&mut self.shoe, events);
+                events.push(Event::DealerHoleCard);
+            }
+        }
+        self.opening_cards = self.players.iter().map(|p| p.hands[0].cards.clone()).collect();
+        self.settle_side_bets(false, events);
+        let upcard = self.dealer.hand().cards[0].numeric_value();
+        for &seat in seats.iter() {
+            let player = &self.players[seat];
+            if upcard == 11 && player.has_blackjack() {
+                self.pending.push_back(Decision::EvenMoney { seat });
+            } else if upcard == 11 && player.max_insurance() > 0 {
+                self.pending.push_back(Decision::Insurance { seat });
+            }
+        }
+        if self.rules.surrender == SurrenderRule::Early && upcard >= 10 {
+            for &seat in seats.iter().filter(|&&seat| !self.players[seat].has_blackjack()) {
+                self.pending.push_back(Decision::EarlySurrender { seat });
+            }
+        }
+        if upcard == 11 {
+            events.push(Event::InsuranceOpen);
+        }
+        self.phase = Phase::Insurance;
+        if self.pending.is_empty() {
+            self.check_dealer(events)?;
+        }
+
+        Ok(())
+    }
+
+    /// Peeks for a dealer blackjack where the rules allow it, then starts the player turns.
+    fn check_dealer(&mut self, events: &mut Vec<Event>) -> Result<()> {
+        if self.rules.hole_card.dealer_peeks(&self.dealer.hand().cards[0]) {
+            let blackjack = self.dealer.has_blackjack();
+            events.push(Event::DealerPeeked { blackjack });
+            if blackjack {
+                self.reveal_hole_card(events)?;
+                self.settle_insurance(events);
+                self.phase = Phase::Settlement;
+                return Ok(());
+            }
+            self.settle_insurance(events);
+        }
+        self.phase = Phase::PlayerTurns;
+        self.turn = 0;
+        self.find_turn(events)
+    }
+
+    /// Tells anyone counting cards that the shoe ran dry and took its discards back.
+    fn note_refill(shoe: &mut Shoe, events: &mut Vec<Event>) {
+        if shoe.take_refill() {
+            events.push(Event::ShoeRefilled);
+        }
+    }
+
+    fn needs_decision(hand: &Hand, rules: &TableRules) -> bool {
+        hand.pot() > 0
+            && hand.total() < 21
+            && !hand.is_doubled()
+            && !hand.is_split_aces()
+            && !rules.is_charlie(hand)
+    }
+
+    /// Moves play on to the next hand needing a decision, dealing split hands their
+    /// second card on the way. Hands to the dealer once every hand is finished.
+    fn find_turn(&mut self, events: &mut Vec<Event>) -> Result<()> {
+        while self.turn < self.players.len() {
+            let seat = self.turn;
+            let player = &mut self.players[seat];
+            if player.is_in_pot() {
+                loop {
+                    let hand = player.active_hand();
+                    if player.hand().cards.len() == 1 {
+                        player.get_cards(&mut self.shoe, 1)?;
+                        Table::note_refill(&mut self.shoe, events);
+                        events.push(Event::PlayerCard {
+                            seat,
+                            hand,
+                            card: player.latest_card().clone(),
+                            total: player.hand_total(),
+                        });
+                    }
+                    if Table::needs_decision(player.hand(), &self.rules) {
+                        events.push(Event::TurnStarted { seat, hand });
+                        return Ok(());
+                    }
+                    if !player.next_hand() {
+                        break;
+                    }
+                }
+            }
+            self.turn += 1;
+        }
+        self.phase = Phase::DealerTurn;
+
+        Ok(())
+    }
+
+    fn reveal_hole_card(&mut self, events: &mut Vec<Event>) -> Result<()> {
+        if self.rules.hole_card.deals_hole_card() {
+            events.push(Event::HoleCardRevealed {
+                card: self.dealer.hand().cards[1].clone(),
+                total: self.dealer.hand_total(),
+            });
+        } else {
+            self.draw_dealer_card(events)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_dealer_card(&mut self, events: &mut Vec<Event>) -> Result<()> {
+        self.dealer.get_cards(&mut self.shoe, 1)?;
+        Table::note_refill(&mut self.shoe, events);
+        events.push(Event::DealerCard {
+            card: self.dealer.latest_card().clone(),
+            total: self.dealer.hand_total(),
+        });
+
+        Ok(())
+    }
+
+    /// Settles the side bets due now: on the deal, those that need only the upcard, and
+    /// after the dealer plays, the rest against the dealer's final hand.
+    fn settle_side_bets(&mut self, after_dealer: bool, events: &mut Vec<Event>) {
+        let upcard = Hand::from_cards(self.dealer.hand().cards[..1].to_vec());
+        let dealer = match after_dealer {
+            true => self.dealer.hand(),
+            false => &upcard,
+        };
+        for (seat, player) in self.players.iter_mut().enumerate() {
+            let due = player.side_bets().iter().filter(|(kind, _)| kind.settles_after_dealer() == after_dealer);
+            for (kind, amount) in due.copied().collect::<Vec<_>>() {
+                let outcome = kind.outcome(&self.opening_cards[seat], dealer);
+                let payout = self.rules.side_bet(kind).map_or(0, |side_bet| side_bet.pay(outcome, amount));
+                player.resolve_side_bet(kind, payout).unwrap();
+                events.push(Event::SideBetSettled { seat, kind, outcome, payout });
+            }
+        }
+    }
+
+    fn settle_insurance(&mut self, events: &mut Vec<Event>) {
+        let blackjack = self.dealer.has_blackjack();
+        for (seat, player) in self.players.iter_mut().enumerate() {
+            if player.insurance() > 0 {
+                let payout = player.resolve_insurance(blackjack).unwrap();
+                events.push(Event::InsuranceSettled { seat, payout });
+            }
+        }
+    }
+
+    fn play_dealer(&mut self, events: &mut Vec<Event>) -> Result<()> {
+        self.reveal_hole_card(events)?;
+        self.settle_insurance(events);
+        if self.rules.hole_card == HoleCardRule::European && self.dealer.has_blackjack() {
+            self.original_bets_only = true;
+        } else if self.players.iter().any(|p| p.is_in_pot() || !p.side_bets().is_empty()) {
+            round::play_dealer(self.dealer.hand_mut(), &mut self.shoe, &self.rules, |hand, refilled| {
+                if refilled {
+                    events.push(Event::ShoeRefilled);
+                }
+                events.push(Event::DealerCard { card: hand.cards[hand.cards.len() - 1].clone(), total: hand.total() });
+            })?;
+            let total = self.dealer.hand_total();
+            events.push(if self.dealer.hand().is_busted() { Event::DealerBusts { total } } else { Event::DealerStands { total } });
+        }
+        self.phase = Phase::Settlement;
+
+        Ok(())
+    }
+
+    fn settle(&mut self, events: &mut Vec<Event>) {
+        self.settle_side_bets(true, events);
+        for (seat, player) in self.players.iter_mut().enumerate() {
+            if self.original_bets_only && player.is_in_pot() && !player.hands[0].is_natural() {
+                let refund = player.lose_original_bet().unwrap();
+                events.push(Event::OriginalBetsOnly { seat, refund });
+                continue;
+            }
+            for hand in 0..player.hands.len() {
+                if player.hands[hand].pot() == 0 {
+                    continue;
+                }
+                let result = round::hand_result(&player.hands[hand], self.dealer.hand(), &self.rules);
+                let payout = player.resolve_hand(hand, result).unwrap();
+                events.push(Event::HandSettled { seat, hand, result, payout });
+            }
+        }
+        events.push(Event::RoundEnded);
+        self.phase = Phase::RoundOver;
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::deck::{ Deck, Value };
+    use crate::side_bet::{ SideBet, SideBetOutcome };
+
+    /// A shoe that deals `values` in order, as Spades, followed by a fresh deck.
+    pub fn stacked_shoe(values: &[u8]) -> Shoe {
+        let mut deck = Deck::new();
+        deck.extend(values.iter().rev().map(|&v| Card::_test_card(v)).collect());
+        Shoe::stacked(deck)
+    }
+
+    fn table(rules: TableRules, players: u8, values: &[u8]) -> Table {
+        Table::with_shoe(rules, PlayerList::new(players, 100), 10, stacked_shoe(values))
+    }
+
+    /// Starts a round and bets 10 for every seat, stopping once the cards are dealt.
+    fn deal_round(table: &mut Table) -> Vec<Event> {
+        let mut events = table.start_round().unwrap();
+        while let Some(Decision::Bet { seat }) = table.next_decision() {
+            events.extend(table.place_bet(seat, 10).unwrap());
+        }
+        events.extend(table.advance().unwrap());
+        events.extend(table.advance().unwrap());
+
+        events
+    }
+
+    fn finish_round(table: &mut Table) -> Vec<Event> {
+        let mut events = Vec::new();
+        while table.phase() != Phase::RoundOver {
+            events.extend(table.advance().unwrap());
+        }
+
+        events
+    }
+
+    pub fn play_round_phases() {
+        // Player: 10, 6 then hits a 5; dealer: 9 up, 8 in the hole.
+        let mut table = table(TableRules::vegas_strip(), 1, &[10, 9, 6, 8, 5]);
+        assert_eq!(table.phase(), Phase::RoundOver);
+        assert_eq!(table.advance(), Err(Error::WrongPhase));
+        table.start_round().unwrap();
+        assert_eq!(table.phase(), Phase::Betting);
+        assert_eq!(table.next_decision(), Some(Decision::Bet { seat: 0 }));
+        assert_eq!(table.advance(), Err(Error::NotYourTurn));
+        assert_eq!(table.place_bet(0, 5), Err(Error::BetBelowMinimum));
+        assert_eq!(table.place_bet(1, 10), Err(Error::NotYourTurn));
+        table.place_bet(0, 20).unwrap();
+        table.advance().unwrap();
+        assert_eq!(table.phase(), Phase::Dealing);
+        let events = table.advance().unwrap();
+        assert_eq!(table.phase(), Phase::PlayerTurns);
+        assert!(events.contains(&Event::DealerUpcard { card: Card::_test_card(9) }));
+        assert!(events.contains(&Event::DealerHoleCard));
+        assert_eq!(events.last(), Some(&Event::TurnStarted { seat: 0, hand: 0 }));
+        assert_eq!(table.next_decision(), Some(Decision::Action { seat: 0, hand: 0 }));
+        assert_eq!(table.act(Action::Split), Err(Error::IllegalAction));
+        let events = table.act(Action::Hit).unwrap();
+        assert!(events.contains(&Event::PlayerCard { seat: 0, hand: 0, card: Card::_test_card(5), total: 21 }));
+        assert_eq!(table.phase(), Phase::DealerTurn);
+        let events = table.advance().unwrap();
+        assert_eq!(events[0], Event::HoleCardRevealed { card: Card::_test_card(8), total: 17 });
+        assert_eq!(events[1], Event::DealerStands { total: 17 });
+        let events = table.advance().unwrap();
+        assert_eq!(events[0], Event::HandSettled { seat: 0, hand: 0, result: BetResult::Win, payout: 40 });
+        assert_eq!(table.phase(), Phase::RoundOver);
+        assert_eq!(table.players()[0].chips, Some(120));
+        assert_eq!(table.round(), 1);
+    }
+
+    pub fn bust_and_dealer_draws() {
+        // Player 1: 10, 6 busts on a King; player 2: 10, 7 stands; dealer 6, 10 draws a 5.
+        let mut table = table(TableRules::vegas_strip(), 2, &[10, 10, 6, 6, 7, 10, 10, 5]);
+        deal_round(&mut table);
+        let events = table.act(Action::Hit).unwrap();
+        assert!(events.contains(&Event::HandBusted { seat: 0, hand: 0, total: 26 }));
+        assert_eq!(events.last(), Some(&Event::TurnStarted { seat: 1, hand: 0 }));
+        table.act(Action::Stand).unwrap();
+        let events = finish_round(&mut table);
+        assert!(events.contains(&Event::DealerCard { card: Card::_test_card(5), total: 21 }));
+        assert!(events.contains(&Event::HandSettled { seat: 1, hand: 0, result: BetResult::Lose, payout: 0 }));
+        assert_eq!(table.players()[0].chips, Some(90));
+        assert_eq!(table.players()[1].chips, Some(90));
+        table.start_round().unwrap();
+        assert_eq!(table.round(), 2);
+        assert_eq!(table.dealer().hand().cards.len(), 0);
+    }
+
+    pub fn split_hands_in_turn() {
+        // Player splits 8s and gets 3 then 10 on the first hand, 9 on the second; dealer 10, 7.
+        let mut table = table(TableRules::vegas_strip(), 1, &[8, 10, 8, 7, 3, 10, 9]);
+        deal_round(&mut table);
+        let events = table.act(Action::Split).unwrap();
+        assert!(events.contains(&Event::PlayerCard { seat: 0, hand: 0, card: Card::_test_card(3), total: 11 }));
+        assert_eq!(table.next_decision(), Some(Decision::Action { seat: 0, hand: 0 }));
+        let events = table.act(Action::DoubleDown).unwrap();
+        assert!(events.contains(&Event::PlayerCard { seat: 0, hand: 1, card: Card::_test_card(9), total: 17 }));
+        assert_eq!(events.last(), Some(&Event::TurnStarted { seat: 0, hand: 1 }));
+        table.act(Action::Stand).unwrap();
+        let events = finish_round(&mut table);
+        assert!(events.contains(&Event::HandSettled { seat: 0, hand: 0, result: BetResult::Win, payout: 40 }));
+        assert!(events.contains(&Event::HandSettled { seat: 0, hand: 1, result: BetResult::StandOff, payout: 10 }));
+        assert_eq!(table.players()[0].chips, Some(120));
+    }
+
+    pub fn insurance_and_peek() {
+        // Player 1: 10, 9 insures; player 2: Ace, King takes even money; dealer Ace up, King down.
+        let mut table = table(TableRules::vegas_strip(), 2, &[10, 11, 11, 9, 10, 10]);
+        let events = deal_round(&mut table);
+        assert!(events.contains(&Event::InsuranceOpen));
+        assert_eq!(table.phase(), Phase::Insurance);
+        assert_eq!(table.next_decision(), Some(Decision::Insurance { seat: 0 }));
+        assert_eq!(table.insure(0, 6), Err(Error::InsuranceTooLarge));
+        table.insure(0, 5).unwrap();
+        assert_eq!(table.next_decision(), Some(Decision::EvenMoney { seat: 1 }));
+        let events = table.even_money(1, true).unwrap();
+        assert_eq!(events, vec![Event::EvenMoneyPaid { seat: 1, payout: 20 }]);
+        let events = table.advance().unwrap();
+        assert_eq!(events[0], Event::DealerPeeked { blackjack: true });
+        assert!(events.contains(&Event::InsuranceSettled { seat: 0, payout: 15 }));
+        assert_eq!(table.phase(), Phase::Settlement);
+        let events = table.advance().unwrap();
+        assert!(events.contains(&Event::HandSettled { seat: 0, hand: 0, result: BetResult::Lose, payout: 0 }));
+        assert_eq!(table.players()[0].chips, Some(100));
+        assert_eq!(table.players()[1].chips, Some(110));
+    }
+
+    pub fn european_original_bets_only() {
+        // Player doubles 5, 6 and draws a 2; the dealer's Ace is joined by a King after play.
+        let mut table = table(TableRules::european(), 1, &[5, 11, 6, 2, 10]);
+        let events = deal_round(&mut table);
+        assert!(!events.contains(&Event::DealerHoleCard));
+        table.insure(0, 0).unwrap();
+        table.advance().unwrap();
+        table.act(Action::DoubleDown).unwrap();
+        let events = finish_round(&mut table);
+        assert!(events.contains(&Event::DealerCard { card: Card::_test_card(10), total: 21 }));
+        assert!(events.contains(&Event::OriginalBetsOnly { seat: 0, refund: 10 }));
+        assert_eq!(table.players()[0].chips, Some(90));
+
+        // Player splits 8s: the first hand busts on 6, King; the second stands on a King.
+        let mut split = self::table(TableRules::european(), 1, &[8, 11, 8, 6, 10, 10, 10]);
+        deal_round(&mut split);
+        split.insure(0, 0).unwrap();
+        split.advance().unwrap();
+        split.act(Action::Split).unwrap();
+        let events = split.act(Action::Hit).unwrap();
+        assert!(events.contains(&Event::HandBusted { seat: 0, hand: 0, total: 24 }));
+        split.act(Action::Stand).unwrap();
+        let events = finish_round(&mut split);
+        // The bust already cost the original bet, so the second hand's stake comes back.
+        assert!(events.contains(&Event::OriginalBetsOnly { seat: 0, refund: 10 }));
+        assert_eq!(split.players()[0].chips, Some(90));
+    }
+
+    pub fn dealer_hits_soft_17_at_table() {
+        // Player stands on 10, 8; the dealer turns over Ace, 6 and hits soft 17 to 20.
+        let mut table = table(TableRules::downtown_vegas(), 1, &[10, 6, 8, 11, 3]);
+        deal_round(&mut table);
+        table.act(Action::Stand).unwrap();
+        let events = finish_round(&mut table);
+        assert_eq!(events[0], Event::HoleCardRevealed { card: Card::_test_card(11), total: 17 });
+        assert_eq!(events[1], Event::DealerCard { card: Card::_test_card(3), total: 20 });
+        assert!(events.contains(&Event::HandSettled { seat: 0, hand: 0, result: BetResult::Lose, payout: 0 }));
+    }
+
+    pub fn early_surrender_decisions() {
+        let rules = TableRules { surrender: SurrenderRule::Early, ..TableRules::vegas_strip() };
+        let mut table = table(rules, 1, &[10, 10, 6, 11]);
+        deal_round(&mut table);
+        assert_eq!(table.next_decision(), Some(Decision::EarlySurrender { seat: 0 }));
+        let events = table.early_surrender(0, true).unwrap();
+        assert_eq!(events, vec![Event::Surrendered { seat: 0, hand: 0, refund: 5 }]);
+        let events = finish_round(&mut table);
+        assert_eq!(events[0], Event::DealerPeeked { blackjack: true });
+        assert_eq!(table.players()[0].chips, Some(95));
+    }
+
+    pub fn seeded_deals() {
+        let deal = |seed: u64| {
+            let shoe = Shoe::seeded(6, 0.75, seed);
+            let mut table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(1, 100), 10, shoe);
+            deal_round(&mut table);
+            table
+        };
+        let table = deal(99);
+        assert_eq!(format!("{}", table.players()[0].hand()), "10 of Spades, Jack of Spades (total: 20)");
+        assert_eq!(format!("{}", table.upcard().unwrap()), "King of Spades");
+        assert_eq!(deal(99).players()[0].hand().cards, table.players()[0].hand().cards);
+        assert_ne!(deal(100).players()[0].hand().cards, table.players()[0].hand().cards);
+    }
+
+    pub fn shoe_runs_out() {
+        // Three cards and no discards can't deal the player two and the dealer two.
+        let mut deck = Deck::new();
+        deck.deal(49);
+        let mut table = Table::with_shoe(TableRules::vegas_strip(), PlayerList::new(1, 100), 10, Shoe::stacked(deck));
+        table.start_round().unwrap();
+        table.place_bet(0, 10).unwrap();
+        table.advance().unwrap();
+        assert_eq!(table.advance(), Err(Error::ShoeEmpty));
+        assert_eq!(table.shoe().remaining(), 0);
+    }
+
+    pub fn side_bets_after_dealer() {
+        let rules = TableRules {
+            side_bets: vec![SideBet::standard(SideBetKind::LuckyLadies), SideBet::standard(SideBetKind::BusterBlackjack)],
+            ..TableRules::vegas_strip()
+        };
+        let bets = [(SideBetKind::LuckyLadies, 10), (SideBetKind::BusterBlackjack, 10)];
+        let start = |table: &mut Table| {
+            table.start_round().unwrap();
+            table.place_bet(0, 10).unwrap();
+            table.place_side_bets(0, &bets).unwrap();
+            table.advance().unwrap();
+            table.advance().unwrap()
+        };
+
+        // Player splits two Kings and gets 5 and 9; the dealer's 6, King draws a King.
+        let mut split = table(rules.clone(), 1, &[10, 6, 10, 10, 5, 9, 10]);
+        let events = start(&mut split);
+        assert!(!events.iter().any(|e| matches!(e, Event::SideBetSettled { .. })));
+        split.act(Action::Split).unwrap();
+        split.act(Action::Stand).unwrap();
+        split.act(Action::Stand).unwrap();
+        let events = finish_round(&mut split);
+        // Lucky Ladies is settled on the Kings dealt, not the split hands.
+        assert!(events.contains(&Event::SideBetSettled {
+            seat: 0,
+            kind: SideBetKind::LuckyLadies,
+            outcome: Some(SideBetOutcome::MatchedTwenty),
+            payout: 200,
+        }));
+        assert!(events.contains(&Event::SideBetSettled {
+            seat: 0,
+            kind: SideBetKind::BusterBlackjack,
+            outcome: Some(SideBetOutcome::ThreeOrFourCardBust),
+            payout: 30,
+        }));
+        assert_eq!(split.players()[0].chips, Some(330));
+
+        // Player busts 10, 6 on a King, but the dealer still plays out 6, King for the Buster bet.
+        let mut bust = table(rules, 1, &[10, 6, 6, 10, 10, 10]);
+        start(&mut bust);
+        bust.act(Action::Hit).unwrap();
+        let events = finish_round(&mut bust);
+        assert!(events.contains(&Event::DealerBusts { total: 26 }));
+        assert!(events.contains(&Event::SideBetSettled { seat: 0, kind: SideBetKind::LuckyLadies, outcome: None, payout: 0 }));
+        assert_eq!(bust.players()[0].chips, Some(100));
+    }
+}
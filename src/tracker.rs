@@ -0,0 +1,265 @@
+use crate::deck::Card;
+use crate::ev;
+use crate::hand::Hand;
+use crate::player::{ Action, PlayerList };
+use crate::rules::TableRules;
+use crate::strategy;
+use crate::table::{ Decision, Table };
+use std::fmt;
+
+/// How decisions are grouped in the session report.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HandCategory {
+    Hard,
+    Soft,
+    Pair,
+}
+
+impl HandCategory {
+    pub const ALL: [HandCategory; 3] = [HandCategory::Hard, HandCategory::Soft, HandCategory::Pair];
+
+    pub fn of(hand: &Hand) -> HandCategory {
+        if hand.is_pair() {
+            HandCategory::Pair
+        } else if hand.is_soft() {
+            HandCategory::Soft
+        } else {
+            HandCategory::Hard
+        }
+    }
+}
+
+impl fmt::Display for HandCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandCategory::Hard => "hard",
+            HandCategory::Soft => "soft",
+            HandCategory::Pair => "pairs",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A hand waiting on its player's move, captured before the move is made.
+#[derive(Clone, Debug)]
+pub struct Spot {
+    pub seat: usize,
+    pub hand: Hand,
+    pub upcard: Card,
+    pub valid_moves: Vec<Action>,
+}
+
+impl Spot {
+    /// The hand whose turn it is, if the table is in the middle of player turns.
+    pub fn at(table: &Table) -> Option<Spot> {
+        let Some(Decision::Action { seat, .. }) = table.next_decision() else {
+            return None;
+        };
+
+        Some(Spot {
+            seat,
+            hand: table.players()[seat].hand().clone(),
+            upcard: table.upcard()?.clone(),
+            valid_moves: table.valid_moves(),
+        })
+    }
+}
+
+/// One decision logged against the basic strategy play.
+#[derive(Clone, Debug)]
+pub struct Play {
+    pub spot: Spot,
+    pub category: HandCategory,
+    pub chosen: Action,
+    pub correct: Action,
+    /// Chips of expected value given up by not playing `correct`.
+    pub cost: f64,
+}
+
+impl Play {
+    pub fn is_mistake(&self) -> bool {
+        self.chosen != self.correct
+    }
+}
+
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} on {} against the {}: {} was right, costing {:.2} chips",
+            self.chosen,
+            self.spot.hand,
+            self.spot.upcard,
+            self.correct,
+            self.cost,
+        )
+    }
+}
+
+/// Logs every player decision with what basic strategy would have done.
+#[derive(Default)]
+pub struct MistakeTracker {
+    plays: Vec<Play>,
+}
+
+impl MistakeTracker {
+    pub fn new() -> MistakeTracker {
+        MistakeTracker { plays: Vec::new() }
+    }
+
+    /// Logs `chosen` for `spot`. A mistake costs the expected value lost against the
+    /// basic strategy play, scaled to the chips on the hand.
+    pub fn record(&mut self, spot: Spot, rules: &TableRules, chosen: Action) {
+        let correct = strategy::best_action(&spot.hand, &spot.upcard, rules, &spot.valid_moves);
+        let cost = match chosen == correct {
+            true => 0.0,
+            false => {
+                let evs = ev::action_evs(&spot.hand, &spot.upcard, rules, &[correct.clone(), chosen.clone()]);
+                (evs[0].1 - evs[1].1).max(0.0) * spot.hand.pot() as f64
+            },
+        };
+        self.plays.push(Play { category: HandCategory::of(&spot.hand), spot, chosen, correct, cost });
+    }
+
+    pub fn plays(&self) -> &[Play] {
+        &self.plays
+    }
+
+    /// A report for every seat that made at least one decision.
+    pub fn reports(&self, players: &PlayerList) -> Vec<SessionReport> {
+        players.iter()
+            .enumerate()
+            .map(|(seat, player)| {
+                let plays: Vec<Play> = self.plays.iter()
+                    .filter(|p| p.spot.seat == seat)
+                    .cloned()
+                    .collect();
+                SessionReport { name: player.to_string(), plays }
+            })
+            .filter(|r| !r.plays.is_empty())
+            .collect()
+    }
+}
+
+/// How one player did against basic strategy over a session.
+pub struct SessionReport {
+    pub name: String,
+    plays: Vec<Play>,
+}
+
+impl SessionReport {
+    /// The number of costliest mistakes listed in the report.
+    pub const WORST: usize = 3;
+
+    pub fn decisions(&self) -> usize {
+        self.plays.len()
+    }
+
+    /// Correct decisions and decisions made, for `category` or for every hand.
+    pub fn accuracy(&self, category: Option<HandCategory>) -> (usize, usize) {
+        let plays = self.plays.iter().filter(|p| category.iter().all(|&c| p.category == c));
+        let (correct, total) = plays.fold((0, 0), |(c, t), p| (c + !p.is_mistake() as usize, t + 1));
+
+        (correct, total)
+    }
+
+    pub fn ev_lost(&self) -> f64 {
+        self.plays.iter().map(|p| p.cost).sum()
+    }
+
+    /// The mistakes that gave up the most expected value, worst first.
+    pub fn costliest(&self) -> Vec<&Play> {
+        let mut mistakes: Vec<&Play> = self.plays.iter().filter(|p| p.is_mistake()).collect();
+        mistakes.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+        mistakes.truncate(SessionReport::WORST);
+
+        mistakes
+    }
+}
+
+fn percent(correct: usize, total: usize) -> f64 {
+    100.0 * correct as f64 / total as f64
+}
+
+impl fmt::Display for SessionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (correct, total) = self.accuracy(None);
+        writeln!(
+            f,
+            "{}: {} decisions, {} correct ({:.1}%), {:.2} chips of EV lost",
+            self.name,
+            total,
+            correct,
+            percent(correct, total),
+            self.ev_lost(),
+        )?;
+        for category in HandCategory::ALL {
+            match self.accuracy(Some(category)) {
+                (_, 0) => writeln!(f, "  {}: no decisions", category)?,
+                (correct, total) => writeln!(f, "  {}: {}/{} ({:.1}%)", category, correct, total, percent(correct, total))?,
+            }
+        }
+        let costliest = self.costliest();
+        if costliest.is_empty() {
+            return write!(f, "  no mistakes, well played!");
+        }
+        write!(f, "  costliest mistakes:")?;
+        for play in costliest {
+            write!(f, "\n    {}", play)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use crate::player::PlayerList;
+
+    fn spot(seat: usize, cards: Vec<u8>, up: u8, pot: u32) -> Spot {
+        let mut hand = Hand::_test_hand(cards);
+        hand.pot = pot;
+        Spot {
+            seat,
+            hand,
+            upcard: Card::_test_card(up),
+            valid_moves: vec![Action::Hit, Action::Stand, Action::Surrender, Action::DoubleDown, Action::Split],
+        }
+    }
+
+    pub fn track_mistakes() {
+        let rules = TableRules::vegas_strip();
+        let mut tracker = MistakeTracker::new();
+        tracker.record(spot(0, vec![10, 7], 10, 10), &rules, Action::Stand);
+        tracker.record(spot(0, vec![10, 8], 6, 10), &rules, Action::Hit);
+        tracker.record(spot(0, vec![11, 6], 4, 10), &rules, Action::Hit);
+        tracker.record(spot(0, vec![8, 8], 10, 20), &rules, Action::Stand);
+        tracker.record(spot(1, vec![10, 2], 5, 10), &rules, Action::Stand);
+        assert_eq!(tracker.plays().len(), 5);
+        assert!(!tracker.plays()[0].is_mistake());
+        assert_eq!(tracker.plays()[0].cost, 0.0);
+        assert_eq!(tracker.plays()[1].correct, Action::Stand);
+        assert!(tracker.plays()[1].cost > 5.0);
+        assert_eq!(tracker.plays()[2].category, HandCategory::Soft);
+        assert_eq!(tracker.plays()[3].category, HandCategory::Pair);
+
+        let reports = tracker.reports(&PlayerList::new(3, 100));
+        assert_eq!(reports.len(), 2);
+        let report = &reports[0];
+        assert_eq!(report.name, "Player 1");
+        assert_eq!(report.decisions(), 4);
+        assert_eq!(report.accuracy(None), (1, 4));
+        assert_eq!(report.accuracy(Some(HandCategory::Hard)), (1, 2));
+        assert_eq!(report.accuracy(Some(HandCategory::Pair)), (0, 1));
+        let costliest = report.costliest();
+        assert_eq!(costliest.len(), 3);
+        assert!(costliest[0].cost >= costliest[1].cost && costliest[1].cost >= costliest[2].cost);
+        assert!((report.ev_lost() - costliest.iter().map(|p| p.cost).sum::<f64>()).abs() < 1e-9);
+        assert_eq!(reports[1].accuracy(None), (1, 1));
+        assert!(format!("{}", reports[1]).ends_with("no mistakes, well played!"));
+        assert!(format!("{}", report).starts_with("Player 1: 4 decisions, 1 correct (25.0%)"));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::choose_rules;
+use crate::io::{ get_clamped_user_int, get_user_str };
+use blackjack::{ Action, Counter, CountSystem, Deck, DrillHistory, DrillRecord, Situation, TrainingProgress };
+use blackjack::progress::timestamp;
+use std::{ thread, time };
+
+/// Moves the cursor up a line and clears it, hiding a flashed group of cards.
+const HIDE_LINE: &str = "\x1B[1A\x1B[2K";
+
+fn choose_system() -> CountSystem {
+    let mut prompt = String::from("Which counting system?");
+    for (i, system) in CountSystem::ALL.iter().enumerate() {
+        prompt.push_str(&format!("\n{}: {}", i, system));
+    }
+
+    CountSystem::ALL[get_clamped_user_int::<usize>(Some(&prompt), 0, CountSystem::ALL.len() - 1)]
+}
+
+fn print_history(history: &DrillHistory) {
+    if history.drills.is_empty() {
+        return;
+    }
+    println!("\nYour latest drills:");
+    for drill in history.recent(5) {
+        println!("{}", drill);
+    }
+    if let Some((pace, accuracy)) = history.trend(5) {
+        println!("Average over these: {:.1}s a deck, {:.0}% of counts right", pace, accuracy);
+    }
+    if let Some(best) = history.best_deck_seconds() {
+        println!("Best deck with every count right: {:.1}s", best);
+    }
+}
+
+/// Asks for the running count, returning whether the answer was right.
+fn check_count(counter: &Counter) -> bool {
+    let answer = get_clamped_user_int::<i32>(Some("What is the running count?"), -100, 100);
+    match answer == counter.running() {
+        true => println!("Correct!"),
+        false => println!("Not quite, the running count is {:+}", counter.running()),
+    }
+
+    answer == counter.running()
+}
+
+/// Flashes a shuffled deck a few cards at a time and checks the running count.
+pub fn drill() {
+    let mut history = match DrillHistory::load(DrillHistory::PATH) {
+        Ok(history) => history,
+        Err(e) => {
+            println!("{}, starting a new drill history", e);
+            DrillHistory::default()
+        },
+    };
+    println!("Counting drill");
+    print_history(&history);
+
+    let system = choose_system();
+    let group = get_clamped_user_int::<usize>(Some("How many cards at a time? (1 to 4)"), 1, 4);
+    let millis = get_clamped_user_int::<u64>(
+        Some("How many milliseconds should each group show for? (0 to press enter for the next group)"),
+        0,
+        5000,
+    );
+    let interval = get_clamped_user_int::<usize>(
+        Some("Ask for the count every how many groups? (0 for the end of the deck only)"),
+        0,
+        52,
+    );
+    let goal = match get_clamped_user_int::<u32>(Some("Seconds to beat for a full deck? (0 for no goal)"), 0, 600) {
+        0 => None,
+        n => Some(n as f64),
+    };
+
+    let mut deck = Deck::new();
+    deck.shuffle(&mut rand::thread_rng());
+    // The last card is held back, otherwise a balanced count always ends on zero.
+    let mut cards = deck.deal(51);
+    let mut counter = Counter::new(system, 1);
+    let (mut asked, mut correct, mut groups) = (0, 0, 0);
+    let mut dealing = time::Duration::ZERO;
+    println!("\nReady? Press enter to start");
+    get_user_str(None);
+    while !cards.is_empty() {
+        let shown: Vec<_> = cards.drain(..group.min(cards.len())).collect();
+        let started = time::Instant::now();
+        let names: Vec<String> = shown.iter().map(|c| c.to_string()).collect();
+        println!("{}", names.join("   "));
+        match millis {
+            0 => {
+                get_user_str(None);
+                print!("{}", HIDE_LINE);
+            },
+            n => thread::sleep(time::Duration::from_millis(n)),
+        }
+        print!("{}", HIDE_LINE);
+        dealing += started.elapsed();
+        shown.iter().for_each(|c| counter.observe(c));
+        groups += 1;
+        if interval > 0 && groups % interval == 0 && !cards.is_empty() {
+            asked += 1;
+            correct += check_count(&counter) as u32;
+        }
+    }
+    println!("\nThat's the deck, less one card.");
+    asked += 1;
+    correct += check_count(&counter) as u32;
+
+    let record = DrillRecord {
+        timestamp: timestamp(),
+        system,
+        cards: counter.seen(),
+        seconds: dealing.as_secs_f64(),
+        asked,
+        correct,
+        goal,
+    };
+    println!("\n{}", record);
+    history.record(record);
+    if let Err(e) = history.save(DrillHistory::PATH) {
+        println!("{}", e);
+    }
+}
+
+fn choose_action(valid_moves: &[Action]) -> Action {
+    let mut prompt = String::from("What does basic strategy say?");
+    for (i, valid_move) in valid_moves.iter().enumerate() {
+        prompt.push_str(&format!("\n{}: {}", i, valid_move));
+    }
+
+    valid_moves[get_clamped_user_int::<usize>(Some(&prompt), 0, valid_moves.len() - 1)].clone()
+}
+
+fn print_weakest(progress: &TrainingProgress) {
+    let weakest = progress.weakest(5);
+    if weakest.is_empty() {
+        return;
+    }
+    println!("\nSpots to work on:");
+    for (key, stats) in weakest {
+        println!("{}: {}/{} right", key, stats.correct, stats.seen);
+    }
+}
+
+/// Asks for the basic strategy play on random starting hands, favouring the ones
+/// answered wrongly before.
+pub fn train() {
+    let mut progress = match TrainingProgress::load(TrainingProgress::PATH) {
+        Ok(progress) => progress,
+        Err(e) => {
+            println!("{}, starting over", e);
+            TrainingProgress::default()
+        },
+    };
+    println!("Strategy flashcards");
+    print_weakest(&progress);
+
+    let rules = choose_rules();
+    let questions = get_clamped_user_int::<u32>(Some("How many flashcards? (1 to 100)"), 1, 100);
+    let situations = Situation::all();
+    let mut rng = rand::thread_rng();
+    let mut correct = 0;
+    for n in 1..=questions {
+        let situation = progress.pick(&situations, &mut rng);
+        println!("\nFlashcard {}/{}: {}", n, questions, situation);
+        let answer = situation.answer(&rules);
+        let right = choose_action(&situation.valid_moves(&rules)) == answer;
+        match right {
+            true => println!("Correct!"),
+            false => println!("Not quite, basic strategy says {}", answer),
+        }
+        correct += right as u32;
+        progress.record(&situation, right);
+    }
+    println!("\n{}/{} correct", correct, questions);
+    print_weakest(&progress);
+    if let Err(e) = progress.save(TrainingProgress::PATH) {
+        println!("{}", e);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::deck::{ Card, Value };
+use crate::error::Result;
+use crate::hand::Hand;
+use crate::player::Action;
+use crate::progress::{ load_or_default, save_toml };
+use crate::rules::{ SurrenderRule, TableRules };
+use crate::strategy;
+use crate::tracker::HandCategory;
+use rand::Rng;
+use rand::distributions::{ Distribution, WeightedIndex };
+use serde::{ Deserialize, Serialize };
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A card of value `n`, tens shown as Kings, in a suit picked by `suit`.
+fn card(n: u8, suit: u8) -> Card {
+    let name = match n {
+        11 => String::from("Ace"),
+        10 => String::from("King"),
+        _ => n.to_string(),
+    };
+    let value = match suit % 4 {
+        0 => Value::Spades(n),
+        1 => Value::Hearts(n),
+        2 => Value::Clubs(n),
+        _ => Value::Diamonds(n),
+    };
+
+    Card::new(value, &name)
+}
+
+/// A starting hand against a dealer upcard, the unit the trainer asks about.
+#[derive(Clone, Debug)]
+pub struct Situation {
+    pub hand: Hand,
+    pub upcard: Card,
+}
+
+impl Situation {
+    fn new(first: u8, second: u8, upcard: u8) -> Situation {
+        Situation {
+            hand: Hand::from_cards(vec![card(first, 0), card(second, 1)]),
+            upcard: card(upcard, 2),
+        }
+    }
+
+    /// One situation for every hard total from 5 to 19, soft total from 13 to 20 and
+    /// pair, against every upcard.
+    pub fn all() -> Vec<Situation> {
+        let hard = (5..=19).map(|total| match total {
+            n if n <= 11 => (2, n - 2),
+            n => (10, n - 10),
+        });
+        let soft = (2..=9).map(|n| (11, n));
+        let pairs = (2..=11).map(|n| (n, n));
+        let hands: Vec<(u8, u8)> = hard.chain(soft).chain(pairs).collect();
+        hands.iter()
+            .flat_map(|&(first, second)| (2..=11).map(move |up| Situation::new(first, second, up)))
+            .collect()
+    }
+
+    /// A name shared by every situation with the same category, total and upcard.
+    pub fn key(&self) -> String {
+        let up = match self.upcard.numeric_value() {
+            11 => String::from("A"),
+            n => n.to_string(),
+        };
+        match HandCategory::of(&self.hand) {
+            HandCategory::Pair => format!("pair of {} v {}", self.hand.cards[0].numeric_value(), up),
+            category => format!("{} {} v {}", category, self.hand.total(), up),
+        }
+    }
+
+    /// The moves open on a fresh two-card hand with chips to spare.
+    pub fn valid_moves(&self, rules: &TableRules) -> Vec<Action> {
+        let mut valid_moves = vec![Action::Hit, Action::Stand];
+        if rules.double.allows(self.hand.total()) {
+            valid_moves.push(Action::DoubleDown);
+        }
+        if self.hand.is_pair() {
+            valid_moves.push(Action::Split);
+        }
+        if rules.surrender != SurrenderRule::NotAllowed {
+            valid_moves.push(Action::Surrender);
+        }
+
+        valid_moves
+    }
+
+    /// The basic strategy play under `rules`.
+    pub fn answer(&self, rules: &TableRules) -> Action {
+        strategy::best_action(&self.hand, &self.upcard, rules, &self.valid_moves(rules))
+    }
+}
+
+impl fmt::Display for Situation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} against the dealer's {}", self.hand, self.upcard)
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// How a situation has gone for the player so far.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CardStats {
+    pub seen: u32,
+    pub correct: u32,
+    /// How much more often than a fresh situation this one comes up.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+impl Default for CardStats {
+    fn default() -> Self {
+        CardStats { seen: 0, correct: 0, weight: default_weight() }
+    }
+}
+
+/// Flashcard results by situation, saved between sessions. Situations answered wrongly
+/// come up more often until they are answered right again.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct TrainingProgress {
+    #[serde(default)]
+    pub situations: BTreeMap<String, CardStats>,
+}
+
+impl TrainingProgress {
+    pub const PATH: &'static str = "blackjack_training.toml";
+    /// The most a situation's weight grows to after repeated misses.
+    pub const MAX_WEIGHT: f64 = 32.0;
+
+    pub fn load(path: &str) -> Result<TrainingProgress> {
+        load_or_default(path)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        save_toml(self, path)
+    }
+
+    pub fn weight(&self, situation: &Situation) -> f64 {
+        self.situations.get(&situation.key()).map_or(default_weight(), |s| s.weight)
+    }
+
+    /// Picks the next flashcard, favouring situations with a high weight.
+    pub fn pick<R: Rng>(&self, situations: &[Situation], rng: &mut R) -> Situation {
+        let weights: Vec<f64> = situations.iter().map(|s| self.weight(s)).collect();
+        let index = WeightedIndex::new(&weights).expect("Weights are always positive");
+
+        situations[index.sample(rng)].clone()
+    }
+
+    /// Records an answer: a miss doubles the situation's weight, a hit halves it.
+    pub fn record(&mut self, situation: &Situation, correct: bool) {
+        let stats = self.situations.entry(situation.key()).or_default();
+        stats.seen += 1;
+        if correct {
+            stats.correct += 1;
+            stats.weight = (stats.weight / 2.0).max(1.0);
+        } else {
+            stats.weight = (stats.weight * 2.0).min(TrainingProgress::MAX_WEIGHT);
+        }
+    }
+
+    /// The `n` situations with the highest weight above a fresh one, hardest first.
+    pub fn weakest(&self, n: usize) -> Vec<(&String, &CardStats)> {
+        let mut weak: Vec<(&String, &CardStats)> = self.situations.iter()
+            .filter(|(_, s)| s.weight > default_weight())
+            .collect();
+        weak.sort_by(|a, b| b.1.weight.total_cmp(&a.1.weight));
+        weak.truncate(n);
+
+        weak
+    }
+}
+
+
+#[cfg(test)]
+#[allow(unused)]
+pub mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    pub fn generate_situations() {
+        let situations = Situation::all();
+        assert_eq!(situations.len(), 330);
+        let keys: std::collections::HashSet<String> = situations.iter().map(|s| s.key()).collect();
+        assert_eq!(keys.len(), 330);
+        assert!(situations.iter().all(|s| s.hand.cards.len() == 2 && !s.hand.is_natural()));
+        let pair = situations.iter().find(|s| s.key() == "pair of 8 v A").unwrap();
+        assert_eq!(format!("{}", pair), "8 of Spades, 8 of Hearts (total: 16) against the dealer's Ace of Clubs");
+        let rules = TableRules::vegas_strip();
+        assert_eq!(pair.answer(&rules), Action::Split);
+        let soft = situations.iter().find(|s| s.key() == "soft 18 v 9").unwrap();
+        assert_eq!(soft.answer(&rules), Action::Hit);
+        let hard = situations.iter().find(|s| s.key() == "hard 11 v 6").unwrap();
+        assert_eq!(hard.answer(&rules), Action::DoubleDown);
+        assert_eq!(hard.answer(&TableRules { double: crate::rules::DoubleRule::TenToEleven, ..rules.clone() }), Action::DoubleDown);
+        let nine = situations.iter().find(|s| s.key() == "hard 9 v 4").unwrap();
+        assert_eq!(nine.answer(&TableRules::european()), Action::DoubleDown);
+        assert_eq!(nine.answer(&TableRules { double: crate::rules::DoubleRule::TenToEleven, ..rules }), Action::Hit);
+    }
+
+    pub fn adaptive_weights() {
+        let situations = Situation::all();
+        let mut progress = TrainingProgress::default();
+        let tricky = situations.iter().find(|s| s.key() == "soft 17 v 3").unwrap();
+        for _ in 0..10 {
+            progress.record(tricky, false);
+        }
+        assert_eq!(progress.weight(tricky), TrainingProgress::MAX_WEIGHT);
+        progress.record(tricky, true);
+        assert_eq!(progress.weight(tricky), 16.0);
+        assert_eq!(progress.situations["soft 17 v 3"], CardStats { seen: 11, correct: 1, weight: 16.0 });
+        assert_eq!(progress.weakest(5).len(), 1);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let picks = (0..1000).filter(|_| progress.pick(&situations, &mut rng).key() == "soft 17 v 3").count();
+        // 16 out of a total weight of 345, about 46 in 1000.
+        assert!((25..80).contains(&picks), "{}", picks);
+
+        let path = std::env::temp_dir().join(format!("blackjack_training_{}.toml", std::process::id()));
+        let path = path.to_str().unwrap();
+        progress.save(path).unwrap();
+        assert_eq!(TrainingProgress::load(path), Ok(progress));
+        std::fs::remove_file(path).unwrap();
+    }
+}